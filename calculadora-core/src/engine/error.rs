@@ -0,0 +1,28 @@
+use std::fmt;
+
+/// Errores que deja el motor en su estado; mientras haya uno la calculadora
+/// queda bloqueada hasta pulsar C o CE, como en la de Windows XP.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CalcError {
+    DivisionByZero,
+    Domain,       // p. ej. sqrt de un negativo
+    Overflow,
+    InvalidInput,
+    NotANumber,   // NaN o infinito
+}
+
+impl fmt::Display for CalcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            CalcError::DivisionByZero => "No se puede dividir por cero.",
+            CalcError::Domain => "Entrada no válida para la función.",
+            CalcError::Overflow => "Desbordamiento.",
+            CalcError::InvalidInput => "Entrada no válida.",
+            CalcError::NotANumber => "Resultado no definido.",
+        };
+        f.write_str(text)
+    }
+}
+
+impl std::error::Error for CalcError {}
@@ -0,0 +1,224 @@
+// Funciones del modo científico. Las trascendentes se calculan en f64 y se
+// redondean a 15 cifras al volver a la aritmética activa; potencias enteras,
+// x², x³ y n! son exactas.
+
+use std::str::FromStr;
+
+use bigdecimal::num_bigint::BigInt;
+use bigdecimal::{BigDecimal, ToPrimitive};
+
+use super::number::{Arithmetic, Context, Number};
+use super::CalcError;
+
+// 100 decimales bastan para cualquier precisión razonable del modo decimal
+const PI: &str = "3.1415926535897932384626433832795028841971693993751058209749445923078164062862089986280348253421170679";
+const E: &str = "2.7182818284590452353602874713526624977572470936999595749669676277240766303535475945713821785251664274";
+
+// n! por encima de este valor supera 10^10000
+const MAX_FACTORIAL: u64 = 3248;
+
+/// Unidad de los ángulos de las funciones trigonométricas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Angle {
+    Degrees,
+    Radians,
+    Gradians,
+}
+
+/// Funciones del modo científico de una sola variable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Function {
+    Sin,
+    Cos,
+    Tan,
+    Asin,
+    Acos,
+    Atan,
+    Sinh,
+    Cosh,
+    Tanh,
+    Asinh,
+    Acosh,
+    Atanh,
+    Ln,
+    Log,
+    Exp,
+    Square,
+    Cube,
+    Factorial,
+}
+
+/// Constantes de las teclas π y e.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Constant {
+    Pi,
+    E,
+}
+
+impl Angle {
+    /// deg, rad o grad, como se escribe en la configuración y en calc.
+    pub fn name(self) -> &'static str {
+        match self {
+            Angle::Degrees => "deg",
+            Angle::Radians => "rad",
+            Angle::Gradians => "grad",
+        }
+    }
+
+    // Cuánto mide media vuelta en esta unidad
+    fn half_turn(self) -> f64 {
+        match self {
+            Angle::Degrees => 180.0,
+            Angle::Radians => std::f64::consts::PI,
+            Angle::Gradians => 200.0,
+        }
+    }
+
+    fn unit_to_radians(self, value: f64) -> f64 {
+        value / self.half_turn() * std::f64::consts::PI
+    }
+
+    fn radians_to_unit(self, value: f64) -> f64 {
+        value / std::f64::consts::PI * self.half_turn()
+    }
+}
+
+impl FromStr for Angle {
+    type Err = CalcError;
+
+    fn from_str(name: &str) -> Result<Self, CalcError> {
+        match name {
+            "deg" => Ok(Angle::Degrees),
+            "rad" => Ok(Angle::Radians),
+            "grad" => Ok(Angle::Gradians),
+            _ => Err(CalcError::InvalidInput),
+        }
+    }
+}
+
+impl Context {
+    /// Valor de la constante con la precisión del contexto.
+    pub fn constant(&self, constant: Constant) -> Result<Number, CalcError> {
+        let digits = match constant {
+            Constant::Pi => PI,
+            Constant::E => E,
+        };
+        let decimal = Context { arithmetic: Arithmetic::Decimal, ..*self };
+        let value = match decimal.parse(digits)? {
+            Number::Decimal(value) => decimal.round(value)?,
+            other => other,
+        };
+        Ok(self.convert(&value))
+    }
+
+    /// Aplica la función; las trigonométricas usan la unidad de ángulo del contexto.
+    pub fn function(&self, function: Function, number: &Number) -> Result<Number, CalcError> {
+        match function {
+            Function::Square => return self.operate('×', number, number),
+            Function::Cube => return self.operate('×', &self.operate('×', number, number)?, number),
+            Function::Factorial => return self.factorial(number),
+            _ => {}
+        }
+        let x = self.float_of(number);
+        let result = match function {
+            Function::Sin | Function::Cos | Function::Tan => self.trig(function, x)?,
+            Function::Asin if x.abs() > 1.0 => return Err(CalcError::Domain),
+            Function::Acos if x.abs() > 1.0 => return Err(CalcError::Domain),
+            Function::Asin => self.angle.radians_to_unit(x.asin()),
+            Function::Acos => self.angle.radians_to_unit(x.acos()),
+            Function::Atan => self.angle.radians_to_unit(x.atan()),
+            Function::Sinh => x.sinh(),
+            Function::Cosh => x.cosh(),
+            Function::Tanh => x.tanh(),
+            Function::Asinh => x.asinh(),
+            Function::Acosh if x < 1.0 => return Err(CalcError::Domain),
+            Function::Acosh => x.acosh(),
+            Function::Atanh if x.abs() >= 1.0 => return Err(CalcError::Domain),
+            Function::Atanh => x.atanh(),
+            Function::Ln | Function::Log if x <= 0.0 => return Err(CalcError::Domain),
+            Function::Ln => x.ln(),
+            Function::Log => x.log10(),
+            Function::Exp => x.exp(),
+            Function::Square | Function::Cube | Function::Factorial => unreachable!(),
+        };
+        self.float_result(result)
+    }
+
+    // Trae un resultado calculado en f64; en los modos exactos se redondea a
+    // 15 cifras para que cos(60°) dé 0.5 y no 0.5000000000000001
+    pub(super) fn float_result(&self, value: f64) -> Result<Number, CalcError> {
+        if value.is_nan() {
+            return Err(CalcError::NotANumber);
+        }
+        if value.is_infinite() {
+            return Err(CalcError::Overflow);
+        }
+        if self.arithmetic == Arithmetic::Float {
+            return Ok(Number::Float(value));
+        }
+        let decimal = Context { arithmetic: Arithmetic::Decimal, ..*self };
+        let rounded = decimal.parse(&format!("{:.14e}", value))?;
+        Ok(self.convert(&rounded))
+    }
+
+    // Raíz y-ésima; con índice entero impar admite radicandos negativos
+    pub(super) fn root(&self, number: &Number, index: &Number) -> Result<Number, CalcError> {
+        let (x, n) = (self.float_of(number), self.float_of(index));
+        if n == 0.0 {
+            return Err(CalcError::Domain);
+        }
+        if x < 0.0 {
+            if n.fract() != 0.0 || n.rem_euclid(2.0) != 1.0 {
+                return Err(CalcError::Domain);
+            }
+            return self.float_result(-(-x).powf(1.0 / n));
+        }
+        self.float_result(x.powf(1.0 / n))
+    }
+
+    pub(super) fn float_of(&self, number: &Number) -> f64 {
+        let float = Context { arithmetic: Arithmetic::Float, ..*self };
+        match float.convert(number) {
+            Number::Float(value) => value,
+            _ => f64::NAN,
+        }
+    }
+
+    fn trig(&self, function: Function, x: f64) -> Result<f64, CalcError> {
+        // Los múltiplos exactos de 90° (o 100 grados centesimales) dan valores
+        // exactos en lugar de restos como 1.2e-16
+        if self.angle != Angle::Radians {
+            let quarter = self.angle.half_turn() / 2.0;
+            let steps = x / quarter;
+            if steps.fract() == 0.0 {
+                let step = steps.rem_euclid(4.0) as u8;
+                return match (function, step) {
+                    (Function::Sin, _) => Ok([0.0, 1.0, 0.0, -1.0][step as usize]),
+                    (Function::Cos, _) => Ok([1.0, 0.0, -1.0, 0.0][step as usize]),
+                    (_, 1) | (_, 3) => Err(CalcError::Domain),
+                    _ => Ok(0.0),
+                };
+            }
+        }
+        let radians = self.angle.unit_to_radians(x);
+        Ok(match function {
+            Function::Sin => radians.sin(),
+            Function::Cos => radians.cos(),
+            _ => radians.tan(),
+        })
+    }
+
+    fn factorial(&self, number: &Number) -> Result<Number, CalcError> {
+        let x = self.float_of(number);
+        if x < 0.0 || x.fract() != 0.0 {
+            return Err(CalcError::Domain);
+        }
+        let n = x.to_u64().filter(|n| *n <= MAX_FACTORIAL).ok_or(CalcError::Overflow)?;
+        let product = BigDecimal::from((2..=n).fold(BigInt::from(1), |acc, k| acc * k));
+        match self.arithmetic {
+            Arithmetic::Float => self.float_result(product.to_f64().unwrap_or(f64::INFINITY)),
+            Arithmetic::Decimal => self.round(product),
+            Arithmetic::Rational | Arithmetic::Integer => Ok(self.convert(&Number::Decimal(product))),
+        }
+    }
+}
@@ -0,0 +1,190 @@
+// Aritmética entera del modo programador: complemento a dos sobre el tamaño
+// de palabra elegido, con entrada y salida en hexadecimal, decimal, octal o
+// binario. Los valores se guardan ya extendidos en signo dentro de un i64.
+
+use std::str::FromStr;
+
+use bigdecimal::num_bigint::BigInt;
+use bigdecimal::{BigDecimal, ToPrimitive};
+
+use super::number::{Arithmetic, Context, Number};
+use super::CalcError;
+
+/// Base de entrada y salida del modo programador.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Radix {
+    Hex,
+    Dec,
+    Oct,
+    Bin,
+}
+
+/// Tamaño de palabra del modo programador: 8, 16, 32 o 64 bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WordSize {
+    Byte,
+    Word,
+    DWord,
+    QWord,
+}
+
+impl Radix {
+    /// hex, dec, oct o bin, como se escribe en la configuración y en calc.
+    pub fn name(self) -> &'static str {
+        match self {
+            Radix::Hex => "hex",
+            Radix::Dec => "dec",
+            Radix::Oct => "oct",
+            Radix::Bin => "bin",
+        }
+    }
+
+    /// 16, 10, 8 o 2.
+    pub fn base(self) -> u32 {
+        match self {
+            Radix::Hex => 16,
+            Radix::Dec => 10,
+            Radix::Oct => 8,
+            Radix::Bin => 2,
+        }
+    }
+}
+
+impl FromStr for Radix {
+    type Err = CalcError;
+
+    fn from_str(name: &str) -> Result<Self, CalcError> {
+        match name {
+            "hex" => Ok(Radix::Hex),
+            "dec" => Ok(Radix::Dec),
+            "oct" => Ok(Radix::Oct),
+            "bin" => Ok(Radix::Bin),
+            _ => Err(CalcError::InvalidInput),
+        }
+    }
+}
+
+impl WordSize {
+    /// byte, word, dword o qword, como se escribe en la configuración y en calc.
+    pub fn name(self) -> &'static str {
+        match self {
+            WordSize::Byte => "byte",
+            WordSize::Word => "word",
+            WordSize::DWord => "dword",
+            WordSize::QWord => "qword",
+        }
+    }
+
+    /// 8, 16, 32 o 64.
+    pub fn bits(self) -> u32 {
+        match self {
+            WordSize::Byte => 8,
+            WordSize::Word => 16,
+            WordSize::DWord => 32,
+            WordSize::QWord => 64,
+        }
+    }
+
+    fn mask(self) -> u64 {
+        u64::MAX >> (64 - self.bits())
+    }
+
+    // Recorta a la palabra y extiende el signo del bit más alto
+    pub(super) fn wrap(self, value: i64) -> i64 {
+        let shift = 64 - self.bits();
+        (value << shift) >> shift
+    }
+}
+
+impl FromStr for WordSize {
+    type Err = CalcError;
+
+    fn from_str(name: &str) -> Result<Self, CalcError> {
+        match name {
+            "byte" => Ok(WordSize::Byte),
+            "word" => Ok(WordSize::Word),
+            "dword" => Ok(WordSize::DWord),
+            "qword" => Ok(WordSize::QWord),
+            _ => Err(CalcError::InvalidInput),
+        }
+    }
+}
+
+pub(super) fn is_bitwise(sign: char) -> bool {
+    matches!(sign, '&' | '|' | '⊻' | '«' | '»')
+}
+
+impl Context {
+    /// Si `c` es una cifra válida en la base activa.
+    pub fn is_digit(&self, c: char) -> bool {
+        match self.arithmetic {
+            Arithmetic::Integer => c.is_digit(self.radix.base()),
+            _ => c.is_ascii_digit(),
+        }
+    }
+
+    pub(super) fn parse_integer(&self, text: &str) -> Result<Number, CalcError> {
+        let word = self.word;
+        if self.radix == Radix::Dec {
+            let value = text.parse::<i128>().map_err(|_| CalcError::InvalidInput)?;
+            let min = -(1i128 << (word.bits() - 1));
+            if value < min || value > word.mask() as i128 {
+                return Err(CalcError::Overflow);
+            }
+            return Ok(Number::Integer(word.wrap(value as i64)));
+        }
+        let value = u64::from_str_radix(text, self.radix.base()).map_err(|_| CalcError::InvalidInput)?;
+        if value > word.mask() {
+            return Err(CalcError::Overflow);
+        }
+        Ok(Number::Integer(word.wrap(value as i64)))
+    }
+
+    pub(super) fn format_integer(&self, value: i64) -> String {
+        let bits = (value as u64) & self.word.mask();
+        match self.radix {
+            Radix::Dec => value.to_string(),
+            Radix::Hex => format!("{:X}", bits),
+            Radix::Oct => format!("{:o}", bits),
+            Radix::Bin => format!("{:b}", bits),
+        }
+    }
+
+    pub(super) fn operate_integer(&self, sign: char, l: i64, r: i64) -> Result<Number, CalcError> {
+        let result = match sign {
+            '+' => l.wrapping_add(r),
+            '-' => l.wrapping_sub(r),
+            '×' => l.wrapping_mul(r),
+            '÷' | 'm' if r == 0 => return Err(CalcError::DivisionByZero),
+            '÷' => l.wrapping_div(r),
+            'm' => l.wrapping_rem(r),
+            '^' if r < 0 => return Err(CalcError::Domain),
+            '^' => l.wrapping_pow(r.min(u32::MAX as i64) as u32),
+            '&' => l & r,
+            '|' => l | r,
+            '⊻' => l ^ r,
+            '«' if r < 0 || r >= self.word.bits() as i64 => 0,
+            '«' => l << r,
+            '»' if r < 0 => return Err(CalcError::Domain),
+            '»' => l >> r.min(63),
+            _ => return Err(CalcError::InvalidInput),
+        };
+        Ok(Number::Integer(self.word.wrap(result)))
+    }
+
+    /// Not: complemento a uno dentro de la palabra.
+    pub fn not(&self, number: &Number) -> Result<Number, CalcError> {
+        let integer = Context { arithmetic: Arithmetic::Integer, ..*self };
+        match integer.convert(number) {
+            Number::Integer(value) => Ok(self.convert(&Number::Integer(self.word.wrap(!value)))),
+            _ => Err(CalcError::InvalidInput),
+        }
+    }
+
+    // Trunca hacia cero y se queda con los bits que caben en la palabra
+    pub(super) fn integer_of(&self, value: &BigDecimal) -> i64 {
+        let (digits, _) = value.with_scale(0).into_bigint_and_exponent();
+        let low = digits & BigInt::from(self.word.mask());
+        self.word.wrap(low.to_u64().unwrap_or_default() as i64)
+    }
+}
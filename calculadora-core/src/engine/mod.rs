@@ -0,0 +1,1051 @@
+// Motor de cálculo independiente de la interfaz: guarda el estado de la
+// operación en curso y responde a las mismas entradas que los botones.
+
+mod error;
+mod functions;
+mod integer;
+mod number;
+mod parser;
+mod statistics;
+
+pub use error::CalcError;
+pub use functions::{Angle, Constant, Function};
+pub use integer::{Radix, WordSize};
+pub use number::{Arithmetic, Context, Number};
+pub use parser::evaluate;
+pub use statistics::Stat;
+
+// Cifras del exponente que admite la tecla Exp, como en XP
+const MAX_EXPONENT_DIGITS: usize = 4;
+
+/// Una tecla de la calculadora, la unidad de entrada de [`Engine::press`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Input {
+    /// Una cifra en la base activa: 0-9 y, en hexadecimal, A-F.
+    Num(char),
+    /// Un operador de dos operandos.
+    Operator(Operator),
+    /// =
+    Ans,
+    /// El punto decimal.
+    Dot,
+    /// ±
+    Neg,
+    /// C: borra la operación y el error.
+    Clear,
+    /// CE: borra el número que se está escribiendo.
+    ClearEnd,
+    /// ←
+    Backspace,
+    /// MC, MR, MS, M+ o M- sobre la memoria principal.
+    Memory(Memory),
+    /// Lo mismo sobre otro registro; MS en uno nuevo lo crea.
+    Register(usize, Memory),
+    /// sqrt, 1/x, % o Not sobre el número en pantalla.
+    Special(Special),
+    /// (, solo en modo científico.
+    Open,
+    /// ), solo en modo científico.
+    Close,
+    /// Una función del modo científico sobre el número en pantalla.
+    Function(Function),
+    /// π o e.
+    Constant(Constant),
+    /// Añade el número en pantalla a la lista de datos.
+    Dat,
+    /// Un resumen de la lista de datos.
+    Stat(Stat),
+    /// Empieza a teclear el exponente del número.
+    Exp,
+}
+
+/// Operadores de dos operandos.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    /// xʸ
+    Power,
+    /// ʸ√x, la raíz y-ésima.
+    Root,
+    /// Mod, And, Or, Xor, Lsh y Rsh, del modo programador.
+    Mod,
+    And,
+    Or,
+    Xor,
+    Lsh,
+    Rsh,
+}
+
+impl Operator {
+    // Signo con el que se guarda en la operación y lo lee el analizador
+    fn sign(self) -> char {
+        match self {
+            Operator::Add => '+',
+            Operator::Subtract => '-',
+            Operator::Multiply => '×',
+            Operator::Divide => '÷',
+            Operator::Power => '^',
+            Operator::Root => '√',
+            Operator::Mod => 'm',
+            Operator::And => '&',
+            Operator::Or => '|',
+            Operator::Xor => '⊻',
+            Operator::Lsh => '«',
+            Operator::Rsh => '»',
+        }
+    }
+}
+
+/// Teclas de memoria.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Memory {
+    /// MC: pone a cero la memoria principal o quita un registro extra.
+    Clear,
+    /// MR
+    Recall,
+    /// MS
+    Store,
+    /// M+
+    Add,
+    /// M-
+    Subtract,
+}
+
+/// Teclas que actúan sobre el número en pantalla.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Special {
+    Sqrt,
+    /// 1/x
+    Reciprocal,
+    /// %: un porcentaje del primer operando si hay un operador pendiente.
+    Percent,
+    /// Not, el complemento a uno del modo programador.
+    Not,
+}
+
+/// Un registro de memoria con su nombre.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Register {
+    pub name: String,
+    pub value: String,
+}
+
+impl Register {
+    fn new(name: String) -> Self {
+        Self { name, value: "0".to_string() }
+    }
+}
+
+/// Una operación ya resuelta: los términos tal como se escribieron y su resultado.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    /// Operandos, operadores y paréntesis, en el formato del contexto.
+    pub tokens: Vec<String>,
+    pub result: String,
+}
+
+impl Entry {
+    /// Si se puede recuperar con `context`: números que se leen, operadores y
+    /// paréntesis que cierran bien. Sirve para descartar historiales guardados rotos.
+    pub fn is_valid(&self, context: &Context) -> bool {
+        let mut depth = 0usize;
+        for token in &self.tokens {
+            match token.as_str() {
+                "(" => depth += 1,
+                ")" => match depth.checked_sub(1) {
+                    Some(d) => depth = d,
+                    None => return false,
+                },
+                t if is_operator(t) => {},
+                t if context.parse(t).is_err() => return false,
+                _ => {}
+            }
+        }
+        depth == 0 && context.parse(&self.result).is_ok()
+    }
+}
+
+/// Estado de la calculadora: la operación en curso, la memoria, la lista de
+/// datos y el historial. Responde a las mismas teclas que la interfaz.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Engine {
+    left: String,
+    right: String,
+    sign: String,
+    shadow: bool,
+    // Cómo se muestra `right` cuando es el resultado de una función o de MR,
+    // como sqrt(16) en 9 + sqrt(16); vacío mientras se teclea
+    label: String,
+    memory: Vec<Register>, // el primero es el de las teclas MC, MR, MS, M+ y M-
+    data: Vec<String>, // lista de datos del modo estadístico
+    history: Vec<Entry>,
+    context: Context,
+    error: Option<CalcError>,
+    // Modo científico: la operación se acumula en `tokens` y se evalúa con
+    // precedencia al pulsar =, mientras `left` es el número que se escribe
+    precedence: bool,
+    tokens: Vec<String>,
+    // En modo científico, `left` es un resultado (de una función, una constante,
+    // MR...) y no se edita: la siguiente cifra empieza otro número
+    computed: bool,
+    // En modo científico, el último operador y operando del último =, que se
+    // repiten al pulsarlo otra vez
+    repeat: Vec<String>,
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self {
+            left: String::from("0"),
+            right: String::new(),
+            sign: String::new(),
+            shadow: false,
+            label: String::new(),
+            memory: vec![Register::new("M".to_string())],
+            data: Vec::new(),
+            history: Vec::new(),
+            context: Context::default(),
+            error: None,
+            precedence: false,
+            tokens: Vec::new(),
+            computed: false,
+            repeat: Vec::new(),
+        }
+    }
+}
+
+impl Engine {
+    /// Calculadora en modo estándar, con 0 en pantalla y la memoria a cero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// El error que bloquea la calculadora hasta pulsar C o CE, si hay uno.
+    pub fn error(&self) -> Option<CalcError> {
+        self.error
+    }
+
+    /// Aritmética, precisión, ángulos y base con los que se calcula.
+    pub fn context(&self) -> Context {
+        self.context
+    }
+
+    /// Cambia la aritmética o la precisión; lo que hay en pantalla y en memoria
+    /// se reescribe con la nueva representación. La memoria, los datos y el
+    /// historial conservan los valores que la nueva no puede representar (2.5
+    /// en enteros, 1/3 en decimal) y se leen convertidos mientras dure.
+    pub fn set_context(&mut self, context: Context) {
+        let old = self.context;
+        self.context = context;
+        let operands = self.tokens.iter_mut().chain(self.repeat.iter_mut());
+        for text in [&mut self.left, &mut self.right].into_iter().chain(operands) {
+            if let Ok(number) = read(old, text) {
+                *text = context.format(&context.convert(&number));
+            }
+        }
+        let memory = self.memory.iter_mut().map(|r| &mut r.value);
+        let entries = self.history.iter_mut().flat_map(|e| e.tokens.iter_mut().chain(Some(&mut e.result)));
+        for text in memory.chain(self.data.iter_mut()).chain(entries) {
+            let Ok(number) = read(old, text) else { continue };
+            let value = context.convert(&number);
+            let exact = Context { arithmetic: Arithmetic::Rational, ..context };
+            if matches!(number, Number::Integer(_)) || exact.convert(&value) == exact.convert(&number) {
+                *text = context.format(&value);
+            }
+        }
+    }
+
+    /// Registros de memoria tal como se muestran, como pares (nombre, valor).
+    pub fn memory(&self) -> Vec<(String, String)> {
+        self.memory.iter().map(|r| (r.name.clone(), self.context.show(&self.stored(&r.value)))).collect()
+    }
+
+    /// Cambia el nombre de un registro.
+    pub fn rename_register(&mut self, index: usize, name: &str) {
+        if let Some(register) = self.memory.get_mut(index) {
+            register.name = name.to_string();
+        }
+    }
+
+    /// Indicador "M" de XP: hay algo distinto de cero en memoria.
+    pub fn has_memory(&self) -> bool {
+        let ctx = self.context;
+        let zero = ctx.parse("0").ok();
+        self.memory.iter().any(|r| ctx.parse(&self.stored(&r.value)).ok() != zero)
+    }
+
+    /// Valores de la lista de datos tal como se muestran.
+    pub fn data(&self) -> Vec<String> {
+        self.data.iter().map(|text| self.context.show(&self.stored(text))).collect()
+    }
+
+    /// Lleva un dato a la pantalla, como el botón LOAD de XP.
+    pub fn load_data(&mut self, index: usize) {
+        if let Some(value) = self.data.get(index) {
+            if self.error.is_none() {
+                self.set_result(self.stored(value), "");
+            }
+        }
+    }
+
+    /// Quita un valor de la lista de datos.
+    pub fn remove_data(&mut self, index: usize) {
+        if index < self.data.len() {
+            self.data.remove(index);
+        }
+    }
+
+    /// Vacía la lista de datos.
+    pub fn clear_data(&mut self) {
+        self.data.clear();
+    }
+
+    /// Cinta de operaciones, de la más antigua a la más reciente, como
+    /// pares ("2 + 3", "5") listos para mostrar.
+    pub fn history(&self) -> Vec<(String, String)> {
+        let show = |text: &String| symbol(&self.context.show(&self.stored(text))).to_string();
+        self.history
+            .iter()
+            .map(|entry| {
+                let tokens = entry.tokens.iter().map(show).collect::<Vec<_>>();
+                (tokens.join(" "), show(&entry.result))
+            })
+            .collect()
+    }
+
+    /// Lleva el resultado de una operación anterior a la pantalla.
+    pub fn recall_result(&mut self, index: usize) {
+        if let Some(entry) = self.history.get(index) {
+            if self.error.is_none() {
+                self.set_result(self.stored(&entry.result), "");
+            }
+        }
+    }
+
+    /// Vuelve a poner la operación completa, lista para cambiarla o repetirla
+    /// con =. En modo estándar solo caben "a op b"; lo demás recupera el resultado.
+    pub fn recall_expression(&mut self, index: usize) {
+        let Some(entry) = self.history.get(index).cloned() else { return };
+        if self.error.is_some() {
+            return;
+        }
+        let mut tokens = entry.tokens.iter().map(|text| self.stored(text)).collect::<Vec<_>>();
+        let result = self.stored(&entry.result);
+        if self.precedence && tokens.last().is_some_and(|t| t == ")") {
+            // Acaba en paréntesis: se muestra su valor como tras cerrarlo
+            self.tokens = tokens;
+            self.left = result;
+            self.shadow = true;
+            self.computed = false;
+        } else if self.precedence {
+            self.left = tokens.pop().unwrap_or_else(|| "0".to_string());
+            self.tokens = tokens;
+            self.shadow = false;
+            self.computed = false;
+        } else if let [left, sign, right] = &tokens[..] {
+            self.left = left.clone();
+            self.sign = sign.clone();
+            self.right = right.clone();
+            self.label.clear();
+            self.shadow = false;
+        } else {
+            self.recall_result(index);
+        }
+    }
+
+    /// Vacía el historial.
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    /// Memoria e historial tal como se guardan, para conservarlos entre sesiones.
+    pub fn registers(&self) -> &[Register] {
+        &self.memory
+    }
+
+    /// Historial tal como se guarda; ver [`Engine::registers`].
+    pub fn entries(&self) -> &[Entry] {
+        &self.history
+    }
+
+    /// Recupera una sesión anterior; los textos deben estar en el formato del contexto actual.
+    pub fn restore(&mut self, memory: Vec<Register>, history: Vec<Entry>) {
+        if !memory.is_empty() {
+            self.memory = memory;
+        }
+        self.history = history;
+    }
+
+    /// Activa o desactiva la evaluación con precedencia, conservando el valor mostrado.
+    pub fn set_precedence(&mut self, precedence: bool) {
+        self.precedence = precedence;
+        self.sign.clear();
+        self.right.clear();
+        self.label.clear();
+        self.tokens.clear();
+        self.shadow = false;
+        self.computed = false;
+        self.repeat.clear();
+    }
+
+    /// Número que se está viendo o escribiendo, sin la operación pendiente.
+    pub fn value(&self) -> String {
+        self.context.show(self.entry())
+    }
+
+    /// Lo mismo como número; falla a medio escribir un exponente.
+    pub fn number(&self) -> Result<Number, CalcError> {
+        self.context.parse(self.entry())
+    }
+
+    /// Pega texto como hace XP: un número sustituye al operando actual y
+    /// cualquier otra cosa se teclea carácter a carácter. Un número que no
+    /// cabe (1e99999) deja el error en pantalla.
+    pub fn paste(&mut self, text: &str) {
+        let text = text.trim();
+        if self.error.is_some() {
+            return;
+        }
+        let number = match self.context.parse(text) {
+            Ok(number) => number,
+            Err(CalcError::InvalidInput) => {
+                for input in text.chars().filter_map(input_of) {
+                    self.press(input);
+                }
+                return;
+            },
+            Err(error) => {
+                self.error = Some(error);
+                return;
+            },
+        };
+        let value = self.context.format(&number);
+        if self.precedence || self.sign.is_empty() {
+            self.left = value;
+            self.shadow = false;
+        } else if self.shadow {
+            self.clear('0');
+            self.left = value;
+        } else {
+            self.right = value;
+            self.label.clear();
+        }
+    }
+
+    /// Si el último número de la pantalla se está tecleando; ver [`Format::typing`](crate::Format::typing).
+    pub fn is_typing(&self) -> bool {
+        !self.shadow && !self.computed && (self.precedence || self.sign.is_empty() || self.label.is_empty())
+    }
+
+    /// Texto que debe mostrar la pantalla para el estado actual.
+    pub fn display(&self) -> String {
+        let show = |text: &String| self.context.show(text);
+        if self.precedence && !self.tokens.is_empty() {
+            let tokens = self.tokens.iter().map(|t| symbol(&show(t)).to_string());
+            let mut text = tokens.collect::<Vec<_>>().join(" ");
+            if !self.shadow {
+                text.push(' ');
+                text.push_str(&show(&self.left));
+            }
+            text
+        } else if self.shadow || self.precedence {
+            show(&self.left)
+        } else {
+            let right = if self.label.is_empty() { show(&self.right) } else { self.label.clone() };
+            format!("{} {} {}", show(&self.left), symbol(&self.sign), right)
+        }
+    }
+
+    /// Pulsa una tecla. Los errores no se devuelven: quedan en [`Engine::error`].
+    pub fn press(&mut self, input: Input) {
+        if self.error.is_some() {
+            // En modo error solo C y CE desbloquean la calculadora
+            if matches!(input, Input::Clear | Input::ClearEnd) {
+                self.clear('0');
+            }
+            return;
+        }
+        let integer = self.context.arithmetic == Arithmetic::Integer;
+        match input {
+            Input::Num(n) if !self.context.is_digit(n) => return,
+            Input::Dot | Input::Exp if integer => return,
+            _ => {}
+        }
+        let before = (integer && matches!(input, Input::Num(_))).then(|| self.clone());
+        if let Err(e) = self.apply(input) {
+            self.error = Some(e);
+        }
+        // Un dígito que ya no cabe en el tamaño de palabra se descarta
+        if let Some(before) = before {
+            let entry = if self.precedence || self.sign.is_empty() { &self.left } else { &self.right };
+            if self.context.parse(entry).is_err() {
+                *self = before;
+            }
+        }
+    }
+
+    fn apply(&mut self, input: Input) -> Result<(), CalcError> {
+        if let Some(entry) = self.typing().filter(|entry| entry.contains('e')) {
+            // Tras Exp, las cifras, ± y ← actúan sobre el exponente. Uno recuperado
+            // del historial puede no llevar signo (1e5); se edita como 1e+5.
+            let start = entry.rfind('e').unwrap_or_default() + 1;
+            if !entry[start..].starts_with(['+', '-']) {
+                entry.insert(start, '+');
+            }
+            match input {
+                Input::Num(n) => {
+                    if entry.ends_with("e+0") || entry.ends_with("e-0") {
+                        entry.pop();
+                    }
+                    if entry.len() - start <= MAX_EXPONENT_DIGITS {
+                        entry.push(n);
+                    }
+                },
+                Input::Neg => {
+                    let sign = if entry[start..].starts_with('-') { "+" } else { "-" };
+                    entry.replace_range(start..start + 1, sign);
+                },
+                Input::Backspace => {
+                    entry.pop();
+                    if entry.len() == start + 1 {
+                        entry.truncate(start - 1);
+                    }
+                },
+                Input::Dot | Input::Exp => {},
+                _ => return self.apply_entered(input),
+            }
+            return Ok(());
+        }
+        self.apply_entered(input)
+    }
+
+    fn apply_entered(&mut self, input: Input) -> Result<(), CalcError> {
+        if self.precedence && self.apply_infix(input)? {
+            return Ok(());
+        }
+        match input {
+            Input::Num(n) => {
+                if self.sign.is_empty() {
+                    // Tras Dat el siguiente número empieza de nuevo
+                    if &self.left == "0" || self.shadow {
+                        self.left = n.to_string();
+                        self.shadow = false;
+                    } else {
+                        self.left.push(n);
+                    }
+                } else if !self.left.is_empty() && self.shadow {
+                    self.clear(n);
+                } else if &self.right == "0" || !self.label.is_empty() {
+                    // Un resultado no se edita: la cifra empieza otro número
+                    self.right = n.to_string();
+                    self.label.clear();
+                } else {
+                    self.right.push(n);
+                }
+            },
+            Input::Operator(operator) => {
+                let s = operator.sign();
+                if self.sign.is_empty() {
+                    self.sign.push(s);
+                    self.shadow = false;
+                } else {
+                    if !self.shadow && !self.right.is_empty() {
+                        self.calculate()?;
+                    }
+                    self.sign = s.to_string();
+                    self.right.clear();
+                    self.label.clear();
+                    self.shadow = false;
+                }
+            },
+            Input::Ans => {
+                // Como en XP, sin segundo operando se usa el de la pantalla (5 × = eleva
+                // al cuadrado) y tras = se repiten el último operador y operando
+                if !self.sign.is_empty() && !self.left.is_empty() {
+                    if self.right.is_empty() {
+                        self.right = self.left.clone();
+                    }
+                    self.calculate()?;
+                }
+            },
+            Input::Clear => self.clear('0'),
+            Input::ClearEnd => {
+                if self.right.is_empty() {
+                    self.left = "0".to_string();
+                    self.sign.clear();
+                    self.shadow = false;
+                } else {
+                    self.right.clear();
+                    self.label.clear();
+                }
+            },
+            Input::Backspace => {
+                // Los resultados no se editan; el primer operando no se queda vacío
+                // y un "-" solo, de borrar las cifras de un negativo, cuenta como vacío
+                let first = self.sign.is_empty();
+                if let Some(entry) = self.typing() {
+                    entry.pop();
+                    if entry == "-" {
+                        entry.clear();
+                    }
+                    if first && entry.is_empty() {
+                        entry.push('0');
+                    }
+                }
+            },
+            Input::Dot => {
+                if self.sign.is_empty() && self.shadow {
+                    self.left = "0.".to_string();
+                    self.shadow = false;
+                } else if self.sign.is_empty() && !self.left.contains('.') {
+                    self.left.push('.');
+                } else if !self.sign.is_empty() && !self.label.is_empty() {
+                    self.right = "0.".to_string();
+                    self.label.clear();
+                } else if !self.sign.is_empty() && !self.right.contains('.') {
+                    if self.right.is_empty() {
+                        self.right.push('0');
+                    }
+                    self.right.push('.');
+                }
+            },
+            Input::Neg if self.on_right() => {
+                let ctx = self.context;
+                if !self.label.is_empty() || self.right.is_empty() {
+                    let value = ctx.format(&ctx.neg(&ctx.parse(self.entry())?));
+                    self.set_result(value, "-");
+                } else if ctx.arithmetic == Arithmetic::Integer {
+                    self.right = ctx.format(&ctx.neg(&ctx.parse(&self.right)?));
+                } else if let Some(digits) = self.right.strip_prefix('-') {
+                    self.right = digits.to_string();
+                } else if &self.right != "0" && &self.right != "0." {
+                    self.right.insert(0, '-');
+                }
+            },
+            Input::Neg if self.context.arithmetic == Arithmetic::Integer => {
+                // En complemento a dos el signo se calcula, no se antepone
+                if self.sign.is_empty() || self.shadow {
+                    let ctx = self.context;
+                    self.left = ctx.format(&ctx.neg(&ctx.parse(&self.left)?));
+                }
+            },
+            Input::Neg => {
+                if self.sign.is_empty() {
+                    if &self.left != "0" && &self.left != "0." {
+                        if !self.left.starts_with('-') {
+                            self.left.insert(0, '-');
+                        } else {
+                            self.left.remove(0);
+                        }
+                    }
+                } else if self.shadow && &self.left != "0" {
+                    if !self.left.starts_with('-') {
+                        self.left.insert(0, '-');
+                    } else {
+                        self.left.remove(0);
+                    }
+                }
+            },
+            Input::Memory(m) => self.memory_key(0, m)?,
+            Input::Register(index, m) => self.memory_key(index, m)?,
+            Input::Special(Special::Percent) if !self.sign.is_empty() && !self.shadow => {
+                // Con un operador pendiente es un porcentaje del primer operando, como
+                // en XP: 200 + 10 % da 200 + 20 y 50 × 10 % da 50 × 5
+                let ctx = self.context;
+                let left = ctx.parse(&self.left)?;
+                let right = ctx.parse(self.entry())?;
+                let value = ctx.format(&ctx.percent(&ctx.operate('×', &left, &right)?)?);
+                self.set_result(value, "");
+            },
+            Input::Special(s) => {
+                let ctx = self.context;
+                let num = ctx.parse(self.entry())?;
+                let (result, name) = match s {
+                    Special::Sqrt => (ctx.sqrt(&num)?, "sqrt"),
+                    Special::Reciprocal => (ctx.recip(&num)?, "1/"),
+                    Special::Percent => (ctx.percent(&num)?, ""),
+                    Special::Not => (ctx.not(&num)?, "Not"),
+                };
+                self.set_result(ctx.format(&result), name);
+            },
+            Input::Function(f) => {
+                let ctx = self.context;
+                let value = ctx.format(&ctx.function(f, &ctx.parse(&self.left)?)?);
+                self.set_result(value, "");
+            },
+            Input::Constant(c) => {
+                let value = self.context.format(&self.context.constant(c)?);
+                self.set_result(value, "");
+            },
+            Input::Dat => {
+                // Dat guarda el número en pantalla, que es el operando derecho si se está escribiendo
+                let ctx = self.context;
+                self.data.push(ctx.format(&ctx.parse(self.entry())?));
+                if self.sign.is_empty() {
+                    self.shadow = true;
+                }
+            },
+            Input::Stat(s) => {
+                let ctx = self.context;
+                let data = self.data.iter().map(|text| ctx.parse(&self.stored(text))).collect::<Result<Vec<_>, _>>()?;
+                let value = ctx.format(&ctx.statistic(s, &data)?);
+                self.set_result(value, "");
+            },
+            Input::Exp => {
+                if let Some(entry) = self.typing().filter(|entry| !entry.is_empty() && *entry != "-") {
+                    entry.push_str("e+0");
+                }
+            },
+            Input::Open | Input::Close => {}
+        }
+        Ok(())
+    }
+
+    // Entradas con comportamiento propio en modo científico; devuelve false
+    // para las que actúan igual que en el estándar sobre `left`
+    fn apply_infix(&mut self, input: Input) -> Result<bool, CalcError> {
+        match input {
+            Input::Num(n) => {
+                if self.shadow || self.computed || &self.left == "0" {
+                    self.left = n.to_string();
+                    self.shadow = false;
+                    self.computed = false;
+                } else {
+                    self.left.push(n);
+                }
+            },
+            Input::Dot => {
+                if self.shadow || self.computed {
+                    self.left = "0.".to_string();
+                    self.shadow = false;
+                    self.computed = false;
+                } else if !self.left.contains('.') {
+                    self.left.push('.');
+                }
+            },
+            Input::Backspace => {
+                if !self.shadow && !self.computed {
+                    self.left.pop();
+                    if self.left.is_empty() || &self.left == "-" {
+                        self.left = "0".to_string();
+                    }
+                }
+            },
+            Input::Operator(operator) => {
+                let last = self.tokens.last().map(String::as_str);
+                if self.shadow && last.is_some_and(is_operator) {
+                    // Cambia de operador si aún no se escribió el siguiente operando
+                    self.tokens.pop();
+                } else {
+                    self.push_entry();
+                }
+                self.tokens.push(operator.sign().to_string());
+                self.shadow = true;
+            },
+            Input::Open => {
+                let last = self.tokens.last().map(String::as_str);
+                if (!self.shadow && &self.left != "0") || last == Some(")") {
+                    // "2(" y ")(" se entienden como multiplicación
+                    self.push_entry();
+                    self.tokens.push("×".to_string());
+                }
+                self.tokens.push("(".to_string());
+                self.left = "0".to_string();
+                self.shadow = true;
+            },
+            Input::Close => {
+                if self.open_parens() > 0 {
+                    self.push_entry();
+                    let start = self.matching_open().unwrap_or_default();
+                    let group = self.tokens[start + 1..].join(" ");
+                    self.tokens.push(")".to_string());
+                    self.left = self.context.format(&evaluate(&group, &self.context)?);
+                    self.shadow = true;
+                }
+            },
+            Input::Special(Special::Percent) if self.tokens.last().is_some_and(|t| is_operator(t)) => {
+                // Como en la vista estándar, un porcentaje del operando que hay antes
+                // del último operador, sea un número o un paréntesis: 2 + 5 % da 2 + 0.1
+                let ctx = self.context;
+                let end = self.tokens.len() - 1;
+                let start = match self.tokens[..end].last().map(String::as_str) {
+                    Some(")") => self.closed_group(end),
+                    _ => end.checked_sub(1),
+                };
+                let Some(start) = start else { return Ok(false) };
+                let base = evaluate(&self.tokens[start..end].join(" "), &ctx)?;
+                let value = ctx.format(&ctx.percent(&ctx.operate('×', &base, &ctx.parse(&self.left)?)?)?);
+                self.set_result(value, "");
+            },
+            Input::Neg if self.shadow && self.tokens.last().is_some_and(|t| t == ")") => {
+                // Cambia el signo del grupo, que se sustituye como con una función
+                let ctx = self.context;
+                let value = ctx.format(&ctx.neg(&ctx.parse(&self.left)?));
+                self.set_result(value, "");
+            },
+            Input::Ans => {
+                if self.tokens.is_empty() && !self.repeat.is_empty() {
+                    // Como en la vista estándar: 2 + 3 = = da 8
+                    self.push_entry();
+                    self.tokens.extend(self.repeat.clone());
+                } else if !self.tokens.is_empty() {
+                    self.push_entry();
+                    for _ in 0..self.open_parens() {
+                        self.tokens.push(")".to_string());
+                    }
+                    self.repeat = self.last_operation()?;
+                }
+                if !self.tokens.is_empty() {
+                    let result = evaluate(&self.tokens.join(" "), &self.context)?;
+                    self.left = self.context.format(&result);
+                    let tokens = std::mem::take(&mut self.tokens);
+                    self.history.push(Entry { tokens, result: self.left.clone() });
+                    self.shadow = true;
+                }
+            },
+            Input::ClearEnd => {
+                self.left = "0".to_string();
+                self.shadow = false;
+                self.computed = false;
+            },
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    // Añade el número en pantalla como operando, salvo cuando lo que se
+    // muestra es el valor de un paréntesis recién cerrado. Si a ese valor se le
+    // aplicó una función, el resultado sustituye al paréntesis.
+    fn push_entry(&mut self) {
+        let computed = std::mem::take(&mut self.computed);
+        if self.tokens.last().is_some_and(|t| t == ")") {
+            if self.shadow {
+                return;
+            } else if computed {
+                let start = self.closed_group(self.tokens.len()).unwrap_or_default();
+                self.tokens.truncate(start);
+            } else {
+                self.tokens.push("×".to_string());
+            }
+        }
+        // Sin exponentes ni punto final, que el analizador no espera
+        let entry = match self.context.parse(&self.left) {
+            Ok(number) => self.context.format(&number),
+            Err(_) => self.left.clone(),
+        };
+        self.tokens.push(entry);
+    }
+
+    fn open_parens(&self) -> usize {
+        let opened = self.tokens.iter().filter(|t| *t == "(").count();
+        let closed = self.tokens.iter().filter(|t| *t == ")").count();
+        opened.saturating_sub(closed)
+    }
+
+    // Último operador fuera de paréntesis y el valor de lo que le sigue
+    fn last_operation(&self) -> Result<Vec<String>, CalcError> {
+        let mut depth = 0;
+        for (i, token) in self.tokens.iter().enumerate().rev() {
+            match token.as_str() {
+                ")" => depth += 1,
+                "(" => depth -= 1,
+                t if depth == 0 && is_operator(t) => {
+                    let operand = evaluate(&self.tokens[i + 1..].join(" "), &self.context)?;
+                    return Ok(vec![t.to_string(), self.context.format(&operand)]);
+                },
+                _ => {}
+            }
+        }
+        Ok(Vec::new())
+    }
+
+    // Posición del "(" del paréntesis con el que acaban los tokens hasta `end`
+    fn closed_group(&self, end: usize) -> Option<usize> {
+        let mut depth = 0;
+        for (i, token) in self.tokens[..end].iter().enumerate().rev() {
+            match token.as_str() {
+                ")" => depth += 1,
+                "(" if depth == 1 => return Some(i),
+                "(" => depth -= 1,
+                _ => {}
+            }
+        }
+        None
+    }
+
+    // Posición del "(" que cerraría un ")" pulsado ahora
+    fn matching_open(&self) -> Option<usize> {
+        let mut depth = 0;
+        for (i, token) in self.tokens.iter().enumerate().rev() {
+            match token.as_str() {
+                ")" => depth += 1,
+                "(" if depth == 0 => return Some(i),
+                "(" => depth -= 1,
+                _ => {}
+            }
+        }
+        None
+    }
+
+    fn memory_key(&mut self, index: usize, m: Memory) -> Result<(), CalcError> {
+        let ctx = self.context;
+        if index == self.memory.len() && m == Memory::Store {
+            // M1, M2...: el primer número que no use otro registro
+            let name = (1..).map(|n| format!("M{}", n)).find(|name| self.memory.iter().all(|r| &r.name != name));
+            self.memory.push(Register::new(name.unwrap_or_default()));
+        }
+        let Some(register) = self.memory.get(index).map(|r| self.stored(&r.value)) else {
+            return Ok(());
+        };
+        match m {
+            Memory::Clear if index > 0 => { // MC de un registro extra lo quita
+                self.memory.remove(index);
+            },
+            Memory::Clear => self.memory[0].value = "0".to_string(),
+            Memory::Recall => self.set_result(register, ""),
+            Memory::Store | Memory::Add | Memory::Subtract => {
+                let entry = ctx.parse(self.entry())?;
+                let value = match m {
+                    Memory::Add => ctx.operate('+', &ctx.parse(&register)?, &entry)?,
+                    Memory::Subtract => ctx.operate('-', &ctx.parse(&register)?, &entry)?,
+                    _ => entry,
+                };
+                self.memory[index].value = ctx.format(&value);
+                // Como en XP, lo siguiente que se teclee empieza otro número
+                self.set_result(self.entry().clone(), "");
+            },
+        }
+        Ok(())
+    }
+
+    // Valor guardado en el formato del contexto, aunque se conservara en otra aritmética
+    fn stored(&self, text: &str) -> String {
+        let ctx = self.context;
+        match ctx.parse(text) {
+            Ok(_) => text.to_string(),
+            Err(_) => read(ctx, text).map(|number| ctx.format(&ctx.convert(&number))).unwrap_or_else(|_| text.to_string()),
+        }
+    }
+
+    // Número que se está tecleando, si no toca empezar uno nuevo
+    fn typing(&mut self) -> Option<&mut String> {
+        if !self.is_typing() {
+            None
+        } else if self.precedence || self.sign.is_empty() {
+            Some(&mut self.left)
+        } else {
+            Some(&mut self.right)
+        }
+    }
+
+    // Si las teclas actúan sobre el segundo operando de una operación pendiente
+    fn on_right(&self) -> bool {
+        !self.precedence && !self.sign.is_empty() && !self.shadow
+    }
+
+    // Pone un resultado en el operando actual. En el segundo se muestra la
+    // función que lo produjo, como en 9 + sqrt(16); con `function` vacío, solo el valor
+    fn set_result(&mut self, value: String, function: &str) {
+        if self.on_right() {
+            let operand = if self.label.is_empty() { self.context.show(self.entry()) } else { self.label.clone() };
+            self.label = match function {
+                "" => self.context.show(&value),
+                _ => format!("{}({})", function, operand),
+            };
+            self.right = value;
+        } else {
+            self.left = value;
+            // Igual que tras =, la siguiente cifra empieza otro número
+            if self.precedence {
+                self.shadow = false;
+                self.computed = true;
+            } else if self.sign.is_empty() {
+                self.shadow = true;
+            }
+        }
+    }
+
+    fn entry(&self) -> &String {
+        if self.precedence || self.shadow || self.right.is_empty() {
+            &self.left
+        } else {
+            &self.right
+        }
+    }
+
+    fn calculate(&mut self) -> Result<(), CalcError> {
+        let ctx = self.context;
+        let left = ctx.parse(&self.left)?;
+        let right = ctx.parse(&self.right)?;
+        let sign = self.sign.chars().next().ok_or(CalcError::InvalidInput)?;
+        let result = ctx.operate(sign, &left, &right)?;
+        let tokens = vec![self.left.clone(), self.sign.clone(), self.right.clone()];
+        self.shadow = true;
+        self.left = ctx.format(&result);
+        self.history.push(Entry { tokens, result: self.left.clone() });
+        Ok(())
+    }
+
+    fn clear(&mut self, c: char) {
+        self.left = c.into();
+        self.sign.clear();
+        self.right.clear();
+        self.label.clear();
+        self.shadow = false;
+        self.error = None;
+        self.tokens.clear();
+        self.computed = false;
+        self.repeat.clear();
+    }
+}
+
+// Lee un texto guardado sin perder nada: también las fracciones y los
+// decimales que se conservaron al cambiar de aritmética
+fn read(ctx: Context, text: &str) -> Result<Number, CalcError> {
+    let exact = Context { arithmetic: Arithmetic::Rational, ..Context::default() };
+    match ctx.arithmetic {
+        Arithmetic::Integer => ctx.parse(text).or_else(|_| exact.parse(text)),
+        _ => exact.parse(text).or_else(|_| ctx.parse(text)),
+    }
+}
+
+// Tecla que corresponde a un carácter escrito o pegado
+fn input_of(c: char) -> Option<Input> {
+    match c {
+        '0'..='9' => Some(Input::Num(c)),
+        'a'..='f' | 'A'..='F' => Some(Input::Num(c.to_ascii_uppercase())),
+        '+' => Some(Input::Operator(Operator::Add)),
+        '-' => Some(Input::Operator(Operator::Subtract)),
+        '×' | '*' => Some(Input::Operator(Operator::Multiply)),
+        '÷' | '/' => Some(Input::Operator(Operator::Divide)),
+        '^' => Some(Input::Operator(Operator::Power)),
+        '=' => Some(Input::Ans),
+        '.' | ',' => Some(Input::Dot),
+        '@' => Some(Input::Special(Special::Sqrt)),
+        'r' => Some(Input::Special(Special::Reciprocal)),
+        '%' => Some(Input::Special(Special::Percent)),
+        '(' => Some(Input::Open),
+        ')' => Some(Input::Close),
+        _ => None,
+    }
+}
+
+fn is_operator(token: &str) -> bool {
+    matches!(token, "+" | "-" | "×" | "÷" | "^" | "√" | "m" | "&" | "|" | "⊻" | "«" | "»")
+}
+
+// Cómo se muestra cada operador en pantalla
+fn symbol(sign: &str) -> &str {
+    match sign {
+        "m" => "Mod",
+        "&" => "And",
+        "|" => "Or",
+        "⊻" => "Xor",
+        "«" => "Lsh",
+        "»" => "Rsh",
+        _ => sign,
+    }
+}
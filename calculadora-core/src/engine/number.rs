@@ -0,0 +1,404 @@
+// Tipos numéricos del motor. Los operandos viajan como texto (igual que en
+// `left` y `right`) y se convierten con el `Context` activo, que decide si se
+// calcula en decimal de precisión arbitraria, en fracciones exactas, en f64
+// (modo rápido) o con enteros de tamaño fijo (modo programador).
+
+use std::num::NonZeroU64;
+use std::str::FromStr;
+
+use bigdecimal::num_bigint::BigInt;
+use bigdecimal::{BigDecimal, RoundingMode, Signed, ToPrimitive, Zero};
+use num_rational::BigRational;
+
+use super::functions::Angle;
+use super::integer::{is_bitwise, Radix, WordSize};
+use super::CalcError;
+
+// Como en la calculadora de XP, magnitudes de 10^10000 o más desbordan
+const MAX_EXPONENT: i64 = 10000;
+// Bits de 10^10000, límite para numerador y denominador de las fracciones
+const MAX_BITS: u64 = 33220;
+
+/// Cómo se calcula.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arithmetic {
+    /// Decimal de precisión arbitraria, con `precision` cifras significativas.
+    Decimal,
+    /// f64, el modo rápido.
+    Float,
+    /// Fracciones exactas.
+    Rational,
+    /// Enteros en complemento a dos del modo programador.
+    Integer,
+}
+
+impl Arithmetic {
+    /// decimal, float, rational o integer, como se escribe en la configuración.
+    pub fn name(self) -> &'static str {
+        match self {
+            Arithmetic::Decimal => "decimal",
+            Arithmetic::Float => "float",
+            Arithmetic::Rational => "rational",
+            Arithmetic::Integer => "integer",
+        }
+    }
+}
+
+impl FromStr for Arithmetic {
+    type Err = CalcError;
+
+    fn from_str(name: &str) -> Result<Self, CalcError> {
+        match name {
+            "decimal" => Ok(Arithmetic::Decimal),
+            "float" => Ok(Arithmetic::Float),
+            "rational" => Ok(Arithmetic::Rational),
+            "integer" => Ok(Arithmetic::Integer),
+            _ => Err(CalcError::InvalidInput),
+        }
+    }
+}
+
+/// Ajustes con los que se leen, calculan y escriben los números. Se crean
+/// con [`Context::default`] y se cambian los campos que hagan falta.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Context {
+    pub arithmetic: Arithmetic,
+    /// Dígitos significativos en modo decimal.
+    pub precision: u64,
+    /// En modo racional, mostrar 1/3 en vez de 0.333…
+    pub fractions: bool,
+    pub angle: Angle,
+    /// Base de entrada y salida en modo entero.
+    pub radix: Radix,
+    pub word: WordSize,
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        Self {
+            arithmetic: Arithmetic::Decimal,
+            precision: 32,
+            fractions: true,
+            angle: Angle::Degrees,
+            radix: Radix::Dec,
+            word: WordSize::QWord,
+        }
+    }
+}
+
+/// Un valor en la representación de una [`Arithmetic`]. Las operaciones entre
+/// valores de distinta aritmética convierten antes a la del contexto.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Number {
+    Float(f64),
+    Decimal(BigDecimal),
+    Rational(BigRational),
+    Integer(i64),
+}
+
+impl Context {
+    /// Lee un número escrito en el formato del contexto: 12.5, 1e-3 o, en modo
+    /// racional, 1/3. Los exponentes de 10^10000 o más desbordan.
+    pub fn parse(&self, text: &str) -> Result<Number, CalcError> {
+        let text = text.trim().trim_end_matches('.');
+        match self.arithmetic {
+            Arithmetic::Float => text.parse::<f64>().map_err(|_| CalcError::InvalidInput).and_then(checked),
+            Arithmetic::Decimal => decimal_from_str(text).map(Number::Decimal),
+            Arithmetic::Rational => {
+                let ratio = match text.split_once('/') {
+                    Some((numer, denom)) => {
+                        let numer = BigInt::from_str(numer.trim()).map_err(|_| CalcError::InvalidInput)?;
+                        let denom = BigInt::from_str(denom.trim()).map_err(|_| CalcError::InvalidInput)?;
+                        if denom.is_zero() {
+                            return Err(CalcError::DivisionByZero);
+                        }
+                        if numer.bits() > MAX_BITS || denom.bits() > MAX_BITS {
+                            return Err(CalcError::Overflow);
+                        }
+                        BigRational::new(numer, denom)
+                    },
+                    None => ratio_from_decimal(&decimal_from_str(text)?),
+                };
+                Ok(Number::Rational(ratio))
+            },
+            Arithmetic::Integer => self.parse_integer(text),
+        }
+    }
+
+    /// Texto que se guarda en el estado; en modo racional siempre es la fracción
+    /// exacta para que al volver a leerlo no se pierda nada.
+    pub fn format(&self, number: &Number) -> String {
+        match number {
+            Number::Float(value) => {
+                let mut text = format!("{:.10}", value);
+                while text.ends_with('0') && text.contains('.') {
+                    text.pop();
+                }
+                if text.ends_with('.') {
+                    text.pop();
+                }
+                text
+            },
+            Number::Decimal(value) => value.normalized().to_plain_string(),
+            Number::Rational(value) => value.to_string(),
+            Number::Integer(value) => self.format_integer(*value),
+        }
+    }
+
+    /// Texto para la pantalla: las fracciones se enseñan en decimal si así se pidió.
+    pub fn show(&self, text: &str) -> String {
+        if self.arithmetic == Arithmetic::Rational && !self.fractions && text.contains('/') {
+            if let Ok(number) = self.parse(text) {
+                return self.decimal_of(&number).normalized().to_plain_string();
+            }
+        }
+        text.to_string()
+    }
+
+    // Aplica un operador por el signo con el que se guarda (ver Operator)
+    pub(crate) fn operate(&self, sign: char, left: &Number, right: &Number) -> Result<Number, CalcError> {
+        if sign == '√' {
+            return self.root(left, right);
+        }
+        if is_bitwise(sign) && self.arithmetic != Arithmetic::Integer {
+            let integer = Context { arithmetic: Arithmetic::Integer, ..*self };
+            let result = integer.operate(sign, &integer.convert(left), &integer.convert(right))?;
+            return Ok(self.convert(&result));
+        }
+        match (left, right) {
+            (Number::Float(l), Number::Float(r)) => {
+                let result = match sign {
+                    '+' => l + r,
+                    '-' => l - r,
+                    '×' => l * r,
+                    '÷' => {
+                        if *r == 0.0 {
+                            return Err(CalcError::DivisionByZero);
+                        }
+                        l / r
+                    },
+                    '^' => l.powf(*r),
+                    'm' if *r == 0.0 => return Err(CalcError::DivisionByZero),
+                    'm' => l % r,
+                    _ => return Err(CalcError::InvalidInput),
+                };
+                checked(result)
+            },
+            (Number::Decimal(l), Number::Decimal(r)) => {
+                let result = match sign {
+                    '+' => l + r,
+                    '-' => l - r,
+                    '×' => l * r,
+                    '÷' => self.divide(l, r)?,
+                    '^' => return self.power(left, r),
+                    'm' if r.is_zero() => return Err(CalcError::DivisionByZero),
+                    'm' => l % r,
+                    _ => return Err(CalcError::InvalidInput),
+                };
+                self.round(result)
+            },
+            (Number::Rational(l), Number::Rational(r)) => {
+                let result = match sign {
+                    '+' => l + r,
+                    '-' => l - r,
+                    '×' => l * r,
+                    '÷' => {
+                        if r.is_zero() {
+                            return Err(CalcError::DivisionByZero);
+                        }
+                        l / r
+                    },
+                    '^' => return self.power(left, &self.decimal_of(right)),
+                    'm' if r.is_zero() => return Err(CalcError::DivisionByZero),
+                    'm' => l % r,
+                    _ => return Err(CalcError::InvalidInput),
+                };
+                bounded(result)
+            },
+            (Number::Integer(l), Number::Integer(r)) => self.operate_integer(sign, *l, *r),
+            _ => self.operate(sign, &self.convert(left), &self.convert(right)),
+        }
+    }
+
+    /// Cambia el signo; en modo entero, dentro de la palabra.
+    pub fn neg(&self, number: &Number) -> Number {
+        match number {
+            Number::Float(value) => Number::Float(-value),
+            Number::Decimal(value) => Number::Decimal(-value),
+            Number::Rational(value) => Number::Rational(-value),
+            Number::Integer(value) => Number::Integer(self.word.wrap(value.wrapping_neg())),
+        }
+    }
+
+    /// Raíz cuadrada, exacta en modo racional si se puede.
+    pub fn sqrt(&self, number: &Number) -> Result<Number, CalcError> {
+        match number {
+            Number::Float(value) if *value < 0.0 => Err(CalcError::Domain),
+            Number::Float(value) => checked(value.sqrt()),
+            Number::Decimal(value) => {
+                let root = value.sqrt_with_context(&self.rounding()).ok_or(CalcError::Domain)?;
+                self.round(root)
+            },
+            Number::Rational(value) => {
+                if value.is_negative() {
+                    return Err(CalcError::Domain);
+                }
+                // Exacta si numerador y denominador son cuadrados perfectos
+                let numer = value.numer().sqrt();
+                let denom = value.denom().sqrt();
+                if &numer * &numer == *value.numer() && &denom * &denom == *value.denom() {
+                    return Ok(Number::Rational(BigRational::new(numer, denom)));
+                }
+                let decimal = Context { arithmetic: Arithmetic::Decimal, ..*self };
+                let root = decimal.sqrt(&decimal.convert(number))?;
+                Ok(self.convert(&root))
+            },
+            Number::Integer(value) if *value < 0 => Err(CalcError::Domain),
+            Number::Integer(value) => Ok(Number::Integer((*value as u64).isqrt() as i64)),
+        }
+    }
+
+    /// 1/x
+    pub fn recip(&self, number: &Number) -> Result<Number, CalcError> {
+        let one = self.parse("1")?;
+        self.operate('÷', &one, number)
+    }
+
+    /// x / 100
+    pub fn percent(&self, number: &Number) -> Result<Number, CalcError> {
+        let hundred = self.parse("100")?;
+        self.operate('÷', number, &hundred)
+    }
+
+    /// Lleva un número calculado en otro modo a la aritmética actual.
+    pub fn convert(&self, number: &Number) -> Number {
+        match (self.arithmetic, number) {
+            (Arithmetic::Float, Number::Float(_))
+            | (Arithmetic::Decimal, Number::Decimal(_))
+            | (Arithmetic::Rational, Number::Rational(_)) => number.clone(),
+            // Con una palabra más pequeña se pierden los bits altos, como en XP
+            (Arithmetic::Integer, Number::Integer(value)) => Number::Integer(self.word.wrap(*value)),
+            (Arithmetic::Float, _) => {
+                Number::Float(self.decimal_of(number).to_f64().unwrap_or(f64::NAN))
+            },
+            (Arithmetic::Decimal, _) => Number::Decimal(self.decimal_of(number)),
+            (Arithmetic::Rational, _) => Number::Rational(ratio_from_decimal(&self.decimal_of(number))),
+            (Arithmetic::Integer, _) => Number::Integer(self.integer_of(&self.decimal_of(number))),
+        }
+    }
+
+    fn decimal_of(&self, number: &Number) -> BigDecimal {
+        match number {
+            // Por la representación más corta, para no arrastrar la expansión binaria
+            Number::Float(value) => BigDecimal::from_str(&value.to_string()).unwrap_or_default(),
+            Number::Decimal(value) => value.clone(),
+            Number::Rational(value) => {
+                let numer = BigDecimal::from(value.numer().clone());
+                let denom = BigDecimal::from(value.denom().clone());
+                self.divide(&numer, &denom)
+                    .map(|quotient| self.rounding().round_decimal(quotient))
+                    .unwrap_or_default()
+            },
+            Number::Integer(value) => BigDecimal::from(*value),
+        }
+    }
+
+    fn rounding(&self) -> bigdecimal::Context {
+        let precision = NonZeroU64::new(self.precision.max(1)).unwrap_or(NonZeroU64::MIN);
+        bigdecimal::Context::new(precision, RoundingMode::HalfEven)
+    }
+
+    pub(super) fn round(&self, value: BigDecimal) -> Result<Number, CalcError> {
+        let value = self.rounding().round_decimal(value);
+        if !value.is_zero() && value.order_of_magnitude().abs() >= MAX_EXPONENT {
+            return Err(CalcError::Overflow);
+        }
+        Ok(Number::Decimal(value))
+    }
+
+    // División larga entera con un dígito "pegajoso" para que el redondeo
+    // final a `precision` dígitos sea correcto
+    fn divide(&self, left: &BigDecimal, right: &BigDecimal) -> Result<BigDecimal, CalcError> {
+        if right.is_zero() {
+            return Err(CalcError::DivisionByZero);
+        }
+        let (l, l_scale) = left.as_bigint_and_exponent();
+        let (r, r_scale) = right.as_bigint_and_exponent();
+        let digits = |n: &BigInt| n.to_string().trim_start_matches('-').len() as i64;
+        let shift = (self.precision as i64 + digits(&r) - digits(&l) + 1).max(0);
+        let numerator = l * BigInt::from(10).pow(shift as u32);
+        let mut quotient = &numerator / &r;
+        let mut scale = l_scale - r_scale + shift;
+        if (&quotient * &r) != numerator {
+            quotient = quotient * 10 + if numerator.sign() == r.sign() { 1 } else { -1 };
+            scale += 1;
+        }
+        Ok(BigDecimal::new(quotient, scale))
+    }
+
+    // Potencias enteras exactas; el resto se resuelve en f64
+    fn power(&self, base: &Number, exponent: &BigDecimal) -> Result<Number, CalcError> {
+        if exponent.is_integer() {
+            if let Some(n) = exponent.to_i64().filter(|n| n.abs() <= MAX_EXPONENT) {
+                let value = match base {
+                    Number::Rational(ratio) => {
+                        if ratio.is_zero() && n < 0 {
+                            return Err(CalcError::DivisionByZero);
+                        }
+                        bounded(ratio.pow(n.abs() as i32))?
+                    },
+                    _ => {
+                        let base = self.decimal_of(base);
+                        if base.is_zero() && n < 0 {
+                            return Err(CalcError::DivisionByZero);
+                        }
+                        self.round(base.powi_with_context(n.abs(), &self.rounding()))?
+                    },
+                };
+                return if n < 0 { self.recip(&value) } else { Ok(value) };
+            }
+        }
+        let exponent = self.float_of(&Number::Decimal(exponent.clone()));
+        self.float_result(self.float_of(base).powf(exponent))
+    }
+}
+
+// Lee un decimal rechazando ya al leerlo los exponentes que desbordarían:
+// operar con 1e9999999 tal cual dejaría la calculadora colgada
+fn decimal_from_str(text: &str) -> Result<BigDecimal, CalcError> {
+    let value = BigDecimal::from_str(text).map_err(|_| CalcError::InvalidInput)?;
+    if !value.is_zero() && value.order_of_magnitude().abs() >= MAX_EXPONENT {
+        return Err(CalcError::Overflow);
+    }
+    Ok(value)
+}
+
+fn ratio_from_decimal(value: &BigDecimal) -> BigRational {
+    let (digits, scale) = value.as_bigint_and_exponent();
+    if scale >= 0 {
+        BigRational::new(digits, BigInt::from(10).pow(scale as u32))
+    } else {
+        BigRational::from_integer(digits * BigInt::from(10).pow((-scale) as u32))
+    }
+}
+
+// Las fracciones no se redondean, pero tampoco se les deja crecer sin límite
+fn bounded(value: BigRational) -> Result<Number, CalcError> {
+    if value.numer().bits() > MAX_BITS || value.denom().bits() > MAX_BITS {
+        return Err(CalcError::Overflow);
+    }
+    Ok(Number::Rational(value))
+}
+
+// Convierte resultados no finitos en el error correspondiente
+fn checked(value: f64) -> Result<Number, CalcError> {
+    if value.is_nan() {
+        Err(CalcError::NotANumber)
+    } else if value.is_infinite() {
+        Err(CalcError::Overflow)
+    } else {
+        Ok(Number::Float(value))
+    }
+}
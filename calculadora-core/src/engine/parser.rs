@@ -0,0 +1,273 @@
+// Analizador de expresiones infijas: texto -> tokens -> árbol -> valor.
+// Respeta la precedencia habitual (^ y √ sobre × ÷ sobre + -), la
+// asociatividad por la derecha de ^, los paréntesis y el menos unario.
+// También entiende funciones (sin 30, sqrt(2), 5!), constantes (pi, e) y las
+// operaciones de bits del modo programador (and, or, xor, not, lsh, rsh, mod).
+
+use super::{Arithmetic, CalcError, Constant, Context, Function, Number, Special};
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Token {
+    Number(String),
+    Op(char),
+    Open,
+    Close,
+    Function(Function),
+    Special(Special), // sqrt y not, delante del operando
+    Constant(Constant),
+    Factorial, // n!, detrás del operando
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Expr {
+    Number(String),
+    Neg(Box<Expr>),
+    Binary(char, Box<Expr>, Box<Expr>),
+    Function(Function, Box<Expr>),
+    Special(Special, Box<Expr>),
+    Constant(Constant),
+}
+
+// El menos unario y las funciones ligan más que × ÷ pero menos que ^, así -2^2 = -4
+const UNARY: u8 = 7;
+
+fn binding(op: char) -> (u8, bool) {
+    match op {
+        '|' => (1, false),
+        '⊻' => (2, false),
+        '&' => (3, false),
+        '«' | '»' => (4, false),
+        '+' | '-' => (5, false),
+        '×' | '÷' | 'm' => (6, false),
+        _ => (8, true), // ^ y √ (raíz y-ésima)
+    }
+}
+
+fn word(name: &str) -> Option<Token> {
+    let token = match name {
+        "mod" => Token::Op('m'),
+        "and" => Token::Op('&'),
+        "or" => Token::Op('|'),
+        "xor" => Token::Op('⊻'),
+        "lsh" => Token::Op('«'),
+        "rsh" => Token::Op('»'),
+        "not" => Token::Special(Special::Not),
+        "sqrt" => Token::Special(Special::Sqrt),
+        "pi" | "π" => Token::Constant(Constant::Pi),
+        "e" => Token::Constant(Constant::E),
+        "sin" => Token::Function(Function::Sin),
+        "cos" => Token::Function(Function::Cos),
+        "tan" => Token::Function(Function::Tan),
+        "asin" => Token::Function(Function::Asin),
+        "acos" => Token::Function(Function::Acos),
+        "atan" => Token::Function(Function::Atan),
+        "sinh" => Token::Function(Function::Sinh),
+        "cosh" => Token::Function(Function::Cosh),
+        "tanh" => Token::Function(Function::Tanh),
+        "asinh" => Token::Function(Function::Asinh),
+        "acosh" => Token::Function(Function::Acosh),
+        "atanh" => Token::Function(Function::Atanh),
+        "ln" => Token::Function(Function::Ln),
+        "log" => Token::Function(Function::Log),
+        "exp" => Token::Function(Function::Exp),
+        _ => return None,
+    };
+    Some(token)
+}
+
+// Los números se leen en la base del contexto: en hexadecimal "ff" es un
+// número, y en modo programador no hay decimales ni exponentes
+pub(crate) fn tokenize(text: &str, ctx: &Context) -> Result<Vec<Token>, CalcError> {
+    let integer = ctx.arithmetic == Arithmetic::Integer;
+    let is_digit = |c: char| if integer { ctx.is_digit(c) } else { c.is_ascii_digit() || c == '.' };
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            },
+            '0'..='9' | '.' => {
+                let mut number = String::new();
+                while let Some(d) = chars.next_if(|d| is_digit(*d)) {
+                    number.push(d.to_ascii_uppercase());
+                }
+                if number.is_empty() {
+                    return Err(CalcError::InvalidInput);
+                }
+                // Exponente, como en 1.5e+3
+                let mut ahead = chars.clone();
+                if !integer && ahead.next() == Some('e') {
+                    let mut exponent = String::from("e");
+                    if let Some(sign @ ('+' | '-')) = ahead.peek().copied() {
+                        exponent.push(sign);
+                        ahead.next();
+                    }
+                    if ahead.peek().is_some_and(char::is_ascii_digit) {
+                        while let Some(d) = ahead.next_if(char::is_ascii_digit) {
+                            exponent.push(d);
+                        }
+                        number.push_str(&exponent);
+                        chars = ahead;
+                    }
+                }
+                tokens.push(Token::Number(number));
+            },
+            c if c.is_alphabetic() => {
+                let mut name = String::new();
+                while let Some(d) = chars.next_if(|d| d.is_alphabetic() || (integer && d.is_ascii_digit())) {
+                    name.extend(d.to_lowercase());
+                }
+                // En hexadecimal las cifras A-F tienen prioridad sobre la constante e
+                if integer && name.chars().all(is_digit) {
+                    tokens.push(Token::Number(name.to_ascii_uppercase()));
+                } else {
+                    tokens.push(word(&name).ok_or(CalcError::InvalidInput)?);
+                }
+            },
+            '+' | '-' | '^' | '√' | '×' | '÷' | '&' | '|' | '⊻' | '«' | '»' => {
+                tokens.push(Token::Op(c));
+                chars.next();
+            },
+            '<' | '>' => {
+                chars.next();
+                if chars.next() != Some(c) {
+                    return Err(CalcError::InvalidInput);
+                }
+                tokens.push(Token::Op(if c == '<' { '«' } else { '»' }));
+            },
+            '*' => {
+                tokens.push(Token::Op('×'));
+                chars.next();
+            },
+            '/' => {
+                tokens.push(Token::Op('÷'));
+                chars.next();
+            },
+            '~' => {
+                tokens.push(Token::Special(Special::Not));
+                chars.next();
+            },
+            '!' => {
+                tokens.push(Token::Factorial);
+                chars.next();
+            },
+            '(' => {
+                tokens.push(Token::Open);
+                chars.next();
+            },
+            ')' => {
+                tokens.push(Token::Close);
+                chars.next();
+            },
+            _ => return Err(CalcError::InvalidInput),
+        }
+    }
+    Ok(tokens)
+}
+
+pub(crate) fn parse(text: &str, ctx: &Context) -> Result<Expr, CalcError> {
+    let mut parser = Parser { tokens: tokenize(text, ctx)?, pos: 0 };
+    let expr = parser.expression(0)?;
+    if parser.pos != parser.tokens.len() {
+        return Err(CalcError::InvalidInput);
+    }
+    Ok(expr)
+}
+
+/// Evalúa una expresión como 2 + 3 × 4, sqrt(2), sin 30 o 5!, con la
+/// precedencia habitual y la aritmética del contexto.
+pub fn evaluate(text: &str, ctx: &Context) -> Result<Number, CalcError> {
+    parse(text, ctx)?.eval(ctx)
+}
+
+impl Expr {
+    pub(crate) fn eval(&self, ctx: &Context) -> Result<Number, CalcError> {
+        match self {
+            Expr::Number(text) => ctx.parse(text),
+            Expr::Neg(inner) => Ok(ctx.neg(&inner.eval(ctx)?)),
+            Expr::Binary(op, left, right) => ctx.operate(*op, &left.eval(ctx)?, &right.eval(ctx)?),
+            Expr::Function(function, inner) => ctx.function(*function, &inner.eval(ctx)?),
+            Expr::Special(Special::Not, inner) => ctx.not(&inner.eval(ctx)?),
+            Expr::Special(_, inner) => ctx.sqrt(&inner.eval(ctx)?),
+            Expr::Constant(name) => ctx.constant(*name),
+        }
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    // Precedence climbing: consume operadores binarios de al menos `min` de precedencia
+    fn expression(&mut self, min: u8) -> Result<Expr, CalcError> {
+        let mut left = self.unary()?;
+        while let Some(Token::Op(op)) = self.tokens.get(self.pos) {
+            let op = *op;
+            let (precedence, right_assoc) = binding(op);
+            if precedence < min {
+                break;
+            }
+            self.pos += 1;
+            let next = if right_assoc { precedence } else { precedence + 1 };
+            let right = self.expression(next)?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr, CalcError> {
+        match self.tokens.get(self.pos).cloned() {
+            Some(Token::Op('-')) => {
+                self.pos += 1;
+                Ok(Expr::Neg(Box::new(self.expression(UNARY)?)))
+            },
+            Some(Token::Op('+')) => {
+                self.pos += 1;
+                self.expression(UNARY)
+            },
+            Some(Token::Function(function)) => {
+                self.pos += 1;
+                Ok(Expr::Function(function, Box::new(self.expression(UNARY)?)))
+            },
+            Some(Token::Special(special)) => {
+                self.pos += 1;
+                Ok(Expr::Special(special, Box::new(self.expression(UNARY)?)))
+            },
+            _ => self.postfix(),
+        }
+    }
+
+    // n! liga más que cualquier operador
+    fn postfix(&mut self) -> Result<Expr, CalcError> {
+        let mut expr = self.primary()?;
+        while self.tokens.get(self.pos) == Some(&Token::Factorial) {
+            self.pos += 1;
+            expr = Expr::Function(Function::Factorial, Box::new(expr));
+        }
+        Ok(expr)
+    }
+
+    fn primary(&mut self) -> Result<Expr, CalcError> {
+        match self.next() {
+            Some(Token::Number(text)) => Ok(Expr::Number(text)),
+            Some(Token::Constant(name)) => Ok(Expr::Constant(name)),
+            Some(Token::Open) => {
+                let inner = self.expression(0)?;
+                match self.next() {
+                    Some(Token::Close) => Ok(inner),
+                    _ => Err(CalcError::InvalidInput),
+                }
+            },
+            _ => Err(CalcError::InvalidInput),
+        }
+    }
+}
@@ -0,0 +1,83 @@
+// Estadística sobre la lista de datos del modo "Sta": los valores se añaden
+// con Dat y se resumen con la aritmética activa, así que la media o la suma
+// de fracciones siguen siendo exactas.
+
+use std::cmp::Ordering;
+
+use super::number::{Context, Number};
+use super::CalcError;
+
+/// Ave, Sum, s y las que añadimos al XP: mediana, varianza, mínimo, máximo y n.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stat {
+    Count,
+    Sum,
+    Mean,
+    Variance,
+    Deviation,
+    Median,
+    Min,
+    Max,
+}
+
+impl Context {
+    /// Resume la lista de datos; sin datos suficientes da [`CalcError::NotANumber`].
+    pub fn statistic(&self, stat: Stat, data: &[Number]) -> Result<Number, CalcError> {
+        match stat {
+            Stat::Count => self.parse(&data.len().to_string()),
+            Stat::Sum => self.sum(data),
+            Stat::Mean => self.mean(data),
+            Stat::Variance => self.variance(data),
+            Stat::Deviation => self.sqrt(&self.variance(data)?),
+            Stat::Median => self.median(data),
+            Stat::Min => data.iter().min_by(|a, b| self.compare(a, b)).cloned().ok_or(CalcError::NotANumber),
+            Stat::Max => data.iter().max_by(|a, b| self.compare(a, b)).cloned().ok_or(CalcError::NotANumber),
+        }
+    }
+
+    fn sum(&self, data: &[Number]) -> Result<Number, CalcError> {
+        data.iter().try_fold(self.parse("0")?, |sum, x| self.operate('+', &sum, x))
+    }
+
+    fn mean(&self, data: &[Number]) -> Result<Number, CalcError> {
+        if data.is_empty() {
+            return Err(CalcError::NotANumber);
+        }
+        self.operate('÷', &self.sum(data)?, &self.parse(&data.len().to_string())?)
+    }
+
+    // Varianza muestral (n - 1), la misma que usa la s de XP
+    fn variance(&self, data: &[Number]) -> Result<Number, CalcError> {
+        if data.len() < 2 {
+            return Err(CalcError::NotANumber);
+        }
+        let mean = self.mean(data)?;
+        let mut squares = self.parse("0")?;
+        for x in data {
+            let deviation = self.operate('-', x, &mean)?;
+            squares = self.operate('+', &squares, &self.operate('×', &deviation, &deviation)?)?;
+        }
+        self.operate('÷', &squares, &self.parse(&(data.len() - 1).to_string())?)
+    }
+
+    fn median(&self, data: &[Number]) -> Result<Number, CalcError> {
+        let mut sorted = data.to_vec();
+        sorted.sort_by(|a, b| self.compare(a, b));
+        let middle = sorted.len() / 2;
+        match sorted.len() {
+            0 => Err(CalcError::NotANumber),
+            n if n % 2 == 1 => Ok(sorted[middle].clone()),
+            _ => self.mean(&sorted[middle - 1..=middle]),
+        }
+    }
+
+    fn compare(&self, a: &Number, b: &Number) -> Ordering {
+        match (a, b) {
+            (Number::Float(a), Number::Float(b)) => a.partial_cmp(b).unwrap_or(Ordering::Equal),
+            (Number::Decimal(a), Number::Decimal(b)) => a.cmp(b),
+            (Number::Rational(a), Number::Rational(b)) => a.cmp(b),
+            (Number::Integer(a), Number::Integer(b)) => a.cmp(b),
+            _ => self.compare(&self.convert(a), &self.convert(b)),
+        }
+    }
+}
@@ -0,0 +1,213 @@
+//! Presentación de los números en pantalla. Solo cambia el texto que se ve;
+//! el motor sigue guardando los operandos sin separadores y con punto decimal.
+
+use std::str::FromStr;
+
+use bigdecimal::{BigDecimal, Zero};
+
+use crate::engine::Radix;
+
+/// Cifras que caben en la pantalla por defecto.
+pub const DIGITS: usize = 20;
+
+/// Cómo se escriben los números en pantalla.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Format {
+    /// Separar los miles.
+    pub grouping: bool,
+    /// Separador decimal.
+    pub decimal: char,
+    /// Separador de miles cuando se agrupa.
+    pub thousands: char,
+    /// F-E: siempre en notación científica.
+    pub exponent: bool,
+    /// A partir de cuántas cifras se pasa a notación científica.
+    pub digits: usize,
+}
+
+impl Default for Format {
+    fn default() -> Self {
+        Self::english()
+    }
+}
+
+impl Format {
+    /// 1 234 567,89, como recomienda la RAE.
+    pub fn spanish() -> Self {
+        Self { grouping: true, decimal: ',', thousands: ' ', exponent: false, digits: DIGITS }
+    }
+
+    /// 1,234,567.89, el formato por defecto.
+    pub fn english() -> Self {
+        Self { grouping: false, decimal: '.', thousands: ',', exponent: false, digits: DIGITS }
+    }
+
+    /// Cambia el separador decimal; el de miles pasa a ser el otro signo.
+    pub fn with_decimal(self, decimal: char) -> Self {
+        let thousands = if decimal == ',' { '.' } else { ',' };
+        Self { decimal, thousands, ..self }
+    }
+
+    /// Texto de la pantalla con los separadores elegidos. En hexadecimal, octal
+    /// y binario se agrupan las cifras de cuatro en cuatro con espacios, como XP.
+    pub fn display(&self, text: &str, radix: Radix) -> String {
+        self.grouped(text, radix, false)
+    }
+
+    /// Como [`Format::display`] para la pantalla mientras se teclea el último
+    /// número: ese se deja sin F-E hasta que se termine.
+    pub fn typing(&self, text: &str, radix: Radix) -> String {
+        self.grouped(text, radix, true)
+    }
+
+    fn grouped(&self, text: &str, radix: Radix, typing: bool) -> String {
+        let text = if radix == Radix::Dec { self.scientific(text, typing) } else { text.to_string() };
+        let (separator, size) = match radix {
+            Radix::Dec => (self.thousands, 3),
+            _ => (' ', 4),
+        };
+        let is_digit = |c: char| c.is_ascii_digit() || (radix == Radix::Hex && ('A'..='F').contains(&c));
+        let mut result = String::new();
+        let mut digits = String::new();
+        let mut decimals = false;
+        for c in text.chars().chain(Some('\0')) {
+            if is_digit(c) {
+                digits.push(c);
+                continue;
+            }
+            // Las cifras tras el punto decimal y las del exponente no se agrupan
+            if decimals || !self.grouping {
+                result.push_str(&digits);
+            } else {
+                for (i, digit) in digits.chars().enumerate() {
+                    if i > 0 && (digits.len() - i).is_multiple_of(size) {
+                        result.push(separator);
+                    }
+                    result.push(digit);
+                }
+            }
+            digits.clear();
+            decimals = c == '.' || (matches!(c, '+' | '-') && result.ends_with('e'));
+            match c {
+                '\0' => {},
+                '.' => result.push(self.decimal),
+                _ => result.push(c),
+            }
+        }
+        result
+    }
+
+    // Pasa a notación científica (1.234e+25) los números que no caben en
+    // `digits` cifras, o todos con F-E; los que sobran de cifras decimales se redondean
+    fn scientific(&self, text: &str, typing: bool) -> String {
+        let mut result = String::new();
+        let mut number = String::new();
+        for c in text.chars().chain(Some('\0')) {
+            if c.is_ascii_digit() || c == '.' || (c == '/' && !number.is_empty() && !number.contains('/')) {
+                number.push(c);
+                continue;
+            }
+            // Un exponente tecleado con Exp se deja tal cual
+            if result.ends_with("e+") || result.ends_with("e-") || (c == 'e' && !number.is_empty()) {
+                result.push_str(&number);
+            } else if let Some((numer, denom)) = number.split_once('/') {
+                result.push_str(&self.fraction(numer, denom));
+            } else if typing && c == '\0' {
+                result.push_str(&Self { exponent: false, ..*self }.fit(&number));
+            } else {
+                result.push_str(&self.fit(&number));
+            }
+            number.clear();
+            if c != '\0' {
+                result.push(c);
+            }
+        }
+        result
+    }
+
+    // Las fracciones se ven tal cual mientras quepan; si no, en decimal
+    fn fraction(&self, numer: &str, denom: &str) -> String {
+        let text = format!("{}/{}", numer, denom);
+        if text.len() <= self.digits.max(1) {
+            return text;
+        }
+        match (BigDecimal::from_str(numer), BigDecimal::from_str(denom)) {
+            (Ok(numer), Ok(denom)) if !denom.is_zero() => self.fit(&(numer / denom).normalized().to_plain_string()),
+            _ => text,
+        }
+    }
+
+    fn fit(&self, number: &str) -> String {
+        let Ok(value) = BigDecimal::from_str(number.trim_end_matches('.')) else {
+            return number.to_string();
+        };
+        let significant = number.trim_start_matches(['0', '.']).replace('.', "");
+        let exponent = value.normalized().order_of_magnitude();
+        let digits = self.digits.max(1) as i64;
+        if !self.exponent && exponent < digits && exponent > -digits {
+            if significant.len() as i64 <= digits {
+                return number.to_string();
+            }
+            // Cabe sin exponente, pero con menos decimales, salvo que al
+            // redondear gane una cifra (99…9,9)
+            let scale = digits - 1 - exponent;
+            let rounded = value.with_scale_round(scale, bigdecimal::RoundingMode::HalfEven).normalized();
+            if rounded.order_of_magnitude() < digits {
+                return rounded.to_plain_string();
+            }
+        }
+        let rounded = value.with_prec(digits as u64).normalized();
+        let (mantissa, _) = rounded.as_bigint_and_exponent();
+        let mantissa = mantissa.to_string();
+        let exponent = rounded.order_of_magnitude();
+        let (first, rest) = mantissa.split_at(1);
+        let sign = if exponent < 0 { '-' } else { '+' };
+        if rest.is_empty() {
+            format!("{}e{}{}", first, sign, exponent.abs())
+        } else {
+            format!("{}.{}e{}{}", first, rest, sign, exponent.abs())
+        }
+    }
+
+    /// Lleva texto escrito o pegado con cualquiera de los dos separadores a la
+    /// forma del motor. En cada número, si aparecen ambos el último es el
+    /// decimal; si uno se repite es de miles; y uno suelto es decimal salvo que
+    /// no sea el de este formato y le sigan justo tres cifras.
+    pub fn input(&self, text: &str) -> String {
+        let mut result = String::new();
+        let mut number = String::new();
+        for c in text.chars().chain(Some('\0')) {
+            if c.is_ascii_digit() || c == '.' || c == ',' {
+                number.push(c);
+                continue;
+            }
+            result.push_str(&self.normalize(&number));
+            number.clear();
+            if c != '\0' {
+                result.push(c);
+            }
+        }
+        result
+    }
+
+    fn normalize(&self, number: &str) -> String {
+        let separators = number.chars().filter(|c| *c == '.' || *c == ',').collect::<Vec<_>>();
+        let decimal = match separators.as_slice() {
+            [] => return number.to_string(),
+            [.., last] if separators.iter().any(|c| c != last) => Some(*last),
+            [_, _, ..] => None,
+            [single] => {
+                let after = number.len() - number.find(*single).unwrap_or_default() - 1;
+                if *single != self.decimal && after == 3 { None } else { Some(*single) }
+            },
+        };
+        number
+            .chars()
+            .filter_map(|c| match c {
+                '.' | ',' if Some(c) == decimal => Some('.'),
+                '.' | ',' => None,
+                _ => Some(c),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,21 @@
+//! Motor de RustiCalculadora como biblioteca: el estado de la calculadora
+//! ([`Engine`], que responde a las mismas teclas que la interfaz), los números
+//! con su aritmética ([`Context`]), el analizador de expresiones ([`evaluate`]),
+//! el formato de pantalla ([`Format`]) y la conversión de unidades ([`units`]).
+//! Lo usan la interfaz gráfica y calc, y cualquier otra herramienta puede
+//! incrustarlo sin depender de iced.
+
+mod engine;
+pub mod format;
+pub mod units;
+
+pub use engine::{
+    evaluate, Angle, Arithmetic, CalcError, Constant, Context, Engine, Entry, Function, Input, Memory, Number, Operator,
+    Radix, Register, Special, Stat, WordSize,
+};
+pub use format::Format;
+
+// Los valores de `Number` son de estos tipos; se reexportan para poder usarlos
+// sin depender de las mismas versiones de bigdecimal y num-rational
+pub use bigdecimal::BigDecimal;
+pub use num_rational::BigRational;
@@ -0,0 +1,87 @@
+//! Conversión de unidades. Las categorías y sus unidades salen de la tabla de
+//! units.txt, incluida en el binario; cada unidad dice cuánto vale en la base
+//! de su categoría y la conversión pasa siempre por esa base.
+
+use std::sync::OnceLock;
+
+use crate::engine::{evaluate, CalcError, Context, Number};
+
+const TABLE: &str = include_str!("units.txt");
+
+/// Una unidad de la tabla.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unit {
+    pub name: String,
+    pub symbol: String,
+    factor: String, // expresiones, se evalúan con la aritmética de cada conversión
+    offset: String,
+}
+
+/// Una magnitud, como la longitud, con las unidades entre las que se convierte.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Category {
+    pub name: String,
+    pub units: Vec<Unit>,
+}
+
+/// Categorías de la tabla incluida, en su orden.
+pub fn categories() -> &'static [Category] {
+    static CATEGORIES: OnceLock<Vec<Category>> = OnceLock::new();
+    CATEGORIES.get_or_init(|| parse(TABLE).unwrap_or_else(|line| panic!("units.txt:{}: línea no válida", line)))
+}
+
+/// Lee una tabla con el formato de units.txt; el error es el número de la línea
+/// que falla: una unidad sin categoría o con campos de menos o de más, un factor
+/// nulo, un factor o desplazamiento que no se puede calcular, o una categoría
+/// sin unidades.
+pub fn parse(text: &str) -> Result<Vec<Category>, usize> {
+    let mut categories: Vec<Category> = Vec::new();
+    let mut header = 0; // línea de la última categoría, para avisar si queda vacía
+    let context = Context::default();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+            if categories.last().is_some_and(|category| category.units.is_empty()) {
+                return Err(header);
+            }
+            categories.push(Category { name: name.trim().to_string(), units: Vec::new() });
+            header = number + 1;
+            continue;
+        }
+        let fields = line.split('|').map(str::trim).collect::<Vec<_>>();
+        let (name, symbol, factor, offset) = match fields[..] {
+            [name, symbol, factor] => (name, symbol, factor, "0"),
+            [name, symbol, factor, offset] => (name, symbol, factor, offset),
+            _ => return Err(number + 1),
+        };
+        // Un factor nulo haría que convertir a esa unidad dividiera por cero
+        let usable = evaluate(factor, &context).is_ok_and(|factor| Ok(factor) != context.parse("0"));
+        if !usable || evaluate(offset, &context).is_err() {
+            return Err(number + 1);
+        }
+        let category = categories.last_mut().ok_or(number + 1)?;
+        category.units.push(Unit {
+            name: name.to_string(),
+            symbol: symbol.to_string(),
+            factor: factor.to_string(),
+            offset: offset.to_string(),
+        });
+    }
+    if categories.last().is_some_and(|category| category.units.is_empty()) {
+        return Err(header);
+    }
+    Ok(categories)
+}
+
+impl Context {
+    /// Pasa `value` de la unidad `from` a `to`, de la misma categoría.
+    pub fn convert_unit(&self, value: &Number, from: &Unit, to: &Unit) -> Result<Number, CalcError> {
+        let base = self.operate('+', value, &evaluate(&from.offset, self)?)?;
+        let base = self.operate('×', &base, &evaluate(&from.factor, self)?)?;
+        let result = self.operate('÷', &base, &evaluate(&to.factor, self)?)?;
+        self.operate('-', &result, &evaluate(&to.offset, self)?)
+    }
+}
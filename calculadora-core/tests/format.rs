@@ -0,0 +1,50 @@
+// Separadores de la pantalla y lectura de números escritos o pegados
+
+use calculadora_core::{Format, Radix};
+
+#[test]
+fn input_normalizes_separators() {
+    let english = Format::english();
+    let spanish = Format::spanish().with_decimal(',');
+    assert_eq!(english.input("1,234.5 + 2"), "1234.5 + 2");
+    assert_eq!(spanish.input("1.234,5 + 2"), "1234.5 + 2");
+    // Un separador repetido es de miles
+    assert_eq!(english.input("1,234,567"), "1234567");
+    assert_eq!(spanish.input("1.234.567"), "1234567");
+    // Uno suelto es decimal, salvo que sea el otro y le sigan tres cifras
+    assert_eq!(english.input("1,234"), "1234");
+    assert_eq!(english.input("1,5"), "1.5");
+    assert_eq!(spanish.input("1,234"), "1.234");
+    assert_eq!(spanish.input("1.234"), "1234");
+    assert_eq!(spanish.input("1.5"), "1.5");
+    assert_eq!(english.input("sqrt(2) × 3"), "sqrt(2) × 3");
+}
+
+#[test]
+fn display_groups_digits() {
+    assert_eq!(Format::spanish().display("1234567.89", Radix::Dec), "1 234 567,89");
+    assert_eq!(Format { grouping: true, ..Format::english() }.display("-1234567.89", Radix::Dec), "-1,234,567.89");
+    assert_eq!(Format::english().display("1234567", Radix::Dec), "1234567");
+    assert_eq!(Format { grouping: true, ..Format::english() }.display("FFFFF", Radix::Hex), "F FFFF");
+}
+
+#[test]
+fn long_numbers_switch_to_exponent() {
+    let english = Format::english();
+    let exponent = Format { exponent: true, ..english };
+    assert_eq!(english.display("1000000000000000000000", Radix::Dec), "1e+21");
+    assert_eq!(english.display("99999999999999999999.9", Radix::Dec), "1e+20");
+    assert_eq!(english.display("12345678901234567890123", Radix::Dec), "1.234567890123456789e+22");
+    assert_eq!(exponent.display("2 + 1234", Radix::Dec), "2e+0 + 1.234e+3");
+    // Con F-E, el número que se teclea se ve tal cual
+    assert_eq!(exponent.typing("2 + 0.", Radix::Dec), "2e+0 + 0.");
+    assert_eq!(exponent.typing("2 + ", Radix::Dec), "2e+0 + ");
+}
+
+#[test]
+fn long_fractions_are_shown_as_decimals() {
+    let english = Format::english();
+    assert_eq!(english.display("-1/3", Radix::Dec), "-1/3");
+    assert_eq!(english.display("2 ÷ 14142135623730950488016887242097/10000000000000000000000000000000", Radix::Dec), "2 ÷ 1.4142135623730950488");
+    assert_eq!(english.display("1/3486784401000000000000", Radix::Dec), "2.8679719907924413133e-22");
+}
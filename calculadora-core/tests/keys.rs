@@ -0,0 +1,258 @@
+// Pruebas que pulsan secuencias de teclas en el motor y comparan la pantalla.
+// Cada fichero de tests/golden tiene una secuencia por línea con la pantalla
+// esperada detrás de "=>"; las líneas vacías y las que empiezan por # se
+// ignoran. Con UPDATE_GOLDEN=1 los ficheros se reescriben con lo obtenido.
+// Las de scientific.txt se pulsan en modo científico, con precedencia.
+//
+// Teclas: números (2, 12.5, 0FF), + - × ÷ * / ^ √, =, ±, C, CE, ←, MC, MR,
+// MS, M+, M-, sqrt, 1/x, %, not, ( ), Exp, pi, e, x², n!, sin, ln, Dat, Ave y Sum.
+
+use std::fs;
+use std::path::PathBuf;
+
+use calculadora_core::{Arithmetic, Constant, Context, Engine, Entry, Function, Input, Memory, Operator, Special, Stat, WordSize};
+
+fn inputs(keys: &str) -> Vec<Input> {
+    let mut inputs = Vec::new();
+    for key in keys.split_whitespace() {
+        let input = match key {
+            "+" => Input::Operator(Operator::Add),
+            "-" => Input::Operator(Operator::Subtract),
+            "×" | "*" => Input::Operator(Operator::Multiply),
+            "÷" | "/" => Input::Operator(Operator::Divide),
+            "^" => Input::Operator(Operator::Power),
+            "√" => Input::Operator(Operator::Root),
+            "=" => Input::Ans,
+            "±" => Input::Neg,
+            "C" => Input::Clear,
+            "CE" => Input::ClearEnd,
+            "←" => Input::Backspace,
+            "MC" => Input::Memory(Memory::Clear),
+            "MR" => Input::Memory(Memory::Recall),
+            "MS" => Input::Memory(Memory::Store),
+            "M+" => Input::Memory(Memory::Add),
+            "M-" => Input::Memory(Memory::Subtract),
+            "sqrt" => Input::Special(Special::Sqrt),
+            "1/x" => Input::Special(Special::Reciprocal),
+            "%" => Input::Special(Special::Percent),
+            "not" => Input::Special(Special::Not),
+            "(" => Input::Open,
+            ")" => Input::Close,
+            "Exp" => Input::Exp,
+            "pi" => Input::Constant(Constant::Pi),
+            "e" => Input::Constant(Constant::E),
+            "x²" => Input::Function(Function::Square),
+            "n!" => Input::Function(Function::Factorial),
+            "sin" => Input::Function(Function::Sin),
+            "ln" => Input::Function(Function::Ln),
+            "Dat" => Input::Dat,
+            "Ave" => Input::Stat(Stat::Mean),
+            "Sum" => Input::Stat(Stat::Sum),
+            _ if key.starts_with(|c: char| c.is_ascii_digit() || c == '.') => {
+                inputs.extend(key.chars().map(|c| if c == '.' { Input::Dot } else { Input::Num(c) }));
+                continue;
+            },
+            _ => panic!("tecla desconocida: {}", key),
+        };
+        inputs.push(input);
+    }
+    inputs
+}
+
+// Pulsa las teclas y devuelve la pantalla sin los espacios del operador vacío, o el error
+fn display(engine: &mut Engine, keys: &str) -> String {
+    for input in inputs(keys) {
+        engine.press(input);
+    }
+    match engine.error() {
+        Some(error) => error.to_string(),
+        None => engine.display().trim().to_string(),
+    }
+}
+
+fn press(keys: &str) -> String {
+    display(&mut Engine::new(), keys)
+}
+
+fn scientific(keys: &str) -> String {
+    let mut engine = Engine::new();
+    engine.set_precedence(true);
+    display(&mut engine, keys)
+}
+
+fn golden(name: &str) {
+    golden_with(name, press);
+}
+
+fn golden_with(name: &str, press: fn(&str) -> String) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden").join(format!("{}.txt", name));
+    let text = fs::read_to_string(&path).unwrap_or_else(|error| panic!("{}: {}", path.display(), error));
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+    let mut failures = Vec::new();
+    let mut lines = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let Some((keys, expected)) = line.split_once("=>").filter(|_| !line.starts_with('#')) else {
+            lines.push(line.to_string());
+            continue;
+        };
+        let actual = press(keys);
+        if actual != expected.trim() {
+            failures.push(format!("{}:{}: {} => {} (se esperaba {})", name, number + 1, keys.trim(), actual, expected.trim()));
+        }
+        lines.push(format!("{}=> {}", keys, actual));
+    }
+    if update {
+        fs::write(&path, lines.join("\n") + "\n").unwrap();
+    } else {
+        assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+    }
+}
+
+#[test]
+fn basic() {
+    golden("basic");
+}
+
+#[test]
+fn editing() {
+    golden("editing");
+}
+
+#[test]
+fn memory() {
+    golden("memory");
+}
+
+#[test]
+fn special() {
+    golden("special");
+}
+
+#[test]
+fn percent() {
+    golden("percent");
+}
+
+#[test]
+fn scientific_mode() {
+    golden_with("scientific", scientific);
+}
+
+#[test]
+fn errors_lock_until_clear() {
+    let mut engine = Engine::new();
+    assert_eq!(display(&mut engine, "1 ÷ 0 ="), "No se puede dividir por cero.");
+    assert_eq!(display(&mut engine, "5 + 2"), "No se puede dividir por cero.");
+    assert_eq!(display(&mut engine, "C 5 + 2 ="), "7");
+}
+
+#[test]
+fn history_records_each_result() {
+    let mut engine = Engine::new();
+    display(&mut engine, "2 + 3 = C 4 × 5 =");
+    let history = engine.history();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[1].1, "20");
+}
+
+#[test]
+fn broken_history_is_rejected() {
+    let broken = Entry { tokens: vec![")".to_string(), ")".to_string()], result: "5".to_string() };
+    assert!(!broken.is_valid(&Context::default()));
+    // Aunque llegue al motor, recuperarlo no lo hace fallar
+    let mut engine = Engine::new();
+    engine.set_precedence(true);
+    engine.restore(Vec::new(), vec![broken]);
+    engine.recall_expression(0);
+    display(&mut engine, "=");
+}
+
+#[test]
+fn registers_are_named() {
+    let mut engine = Engine::new();
+    display(&mut engine, "5");
+    engine.press(Input::Register(1, Memory::Store));
+    engine.press(Input::Register(2, Memory::Store));
+    engine.rename_register(2, "IVA");
+    let names = |engine: &Engine| engine.memory().into_iter().map(|(name, _)| name).collect::<Vec<_>>();
+    assert_eq!(names(&engine), ["M", "M1", "IVA"]);
+    assert_eq!(engine.memory()[2].1, "5");
+    // Un registro nuevo toma el primer nombre libre
+    engine.press(Input::Register(1, Memory::Clear));
+    engine.press(Input::Register(2, Memory::Store));
+    assert_eq!(names(&engine), ["M", "IVA", "M1"]);
+}
+
+#[test]
+fn recalled_exponents_without_sign_can_be_edited() {
+    let entry = Entry { tokens: vec!["1".to_string(), "+".to_string(), "1e5".to_string()], result: "100001".to_string() };
+    let mut engine = Engine::new();
+    engine.restore(Vec::new(), vec![entry]);
+    engine.recall_expression(0);
+    assert_eq!(display(&mut engine, "± ="), "1.00001");
+    engine.recall_expression(0);
+    assert_eq!(display(&mut engine, "← ±"), "1 + -1");
+}
+
+#[test]
+fn integer_mode_keeps_stored_decimals() {
+    let mut engine = Engine::new();
+    display(&mut engine, "2.5 MS 1.5 + 1 = Dat");
+    let decimal = engine.context();
+    let mut integer = decimal;
+    integer.arithmetic = Arithmetic::Integer;
+    engine.set_context(integer);
+    assert_eq!(engine.memory()[0].1, "2");
+    assert_eq!(engine.history()[0], ("1 + 1".to_string(), "2".to_string()));
+    assert_eq!(display(&mut engine, "MR + 1 ="), "3");
+    engine.set_context(decimal);
+    assert_eq!(engine.memory()[0].1, "2.5");
+    assert_eq!(engine.history()[0], ("1.5 + 1".to_string(), "2.5".to_string()));
+    assert_eq!(engine.data(), ["2.5"]);
+}
+
+#[test]
+fn smaller_words_wrap_stored_values() {
+    let mut engine = Engine::new();
+    let mut context = Context::default();
+    context.arithmetic = Arithmetic::Integer;
+    engine.set_context(context);
+    display(&mut engine, "300 MS");
+    context.word = WordSize::Byte;
+    engine.set_context(context);
+    assert_eq!(display(&mut engine, ""), "44");
+    assert_eq!(engine.memory()[0].1, "44");
+    assert_eq!(display(&mut engine, "+ 1 ="), "45");
+}
+
+#[test]
+fn pasted_numbers_that_do_not_fit_overflow() {
+    let mut engine = Engine::new();
+    engine.paste("2 + 3");
+    assert_eq!(display(&mut engine, "="), "5");
+    engine.paste("1e99999");
+    assert_eq!(display(&mut engine, ""), "Desbordamiento.");
+    let mut engine = Engine::new();
+    let mut float = Context::default();
+    float.arithmetic = Arithmetic::Float;
+    engine.set_context(float);
+    engine.paste("1e99999");
+    assert_eq!(display(&mut engine, ""), "Desbordamiento.");
+}
+
+#[test]
+fn exact_values_survive_leaving_rational_mode() {
+    let mut engine = Engine::new();
+    let mut context = Context::default();
+    context.arithmetic = Arithmetic::Rational;
+    let rational = context;
+    engine.set_context(rational);
+    display(&mut engine, "1 ÷ 3 = MS");
+    context.arithmetic = Arithmetic::Decimal;
+    engine.set_context(context);
+    assert_eq!(engine.memory()[0].1, "0.33333333333333333333333333333333");
+    engine.set_context(rational);
+    assert_eq!(engine.memory()[0].1, "1/3");
+    assert_eq!(engine.history()[0].1, "1/3");
+    assert_eq!(display(&mut engine, "C MR × 3 ="), "1");
+}
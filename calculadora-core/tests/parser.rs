@@ -0,0 +1,74 @@
+// Expresiones infijas y las distintas aritméticas del contexto
+
+use calculadora_core::{evaluate, Arithmetic, CalcError, Context, Radix, WordSize};
+
+fn eval_with(context: Context, text: &str) -> Result<String, CalcError> {
+    evaluate(text, &context).map(|number| context.show(&context.format(&number)))
+}
+
+fn context(arithmetic: Arithmetic) -> Context {
+    let mut context = Context::default();
+    context.arithmetic = arithmetic;
+    context
+}
+
+fn eval(text: &str) -> Result<String, CalcError> {
+    eval_with(Context::default(), text)
+}
+
+#[test]
+fn precedence() {
+    assert_eq!(eval("2+3×4-(1÷8)").as_deref(), Ok("13.875"));
+    assert_eq!(eval("2 + 3 * 4 - (1 / 8)").as_deref(), Ok("13.875"));
+    assert_eq!(eval("-2^2").as_deref(), Ok("-4"));
+    assert_eq!(eval("(-2)^2").as_deref(), Ok("4"));
+    assert_eq!(eval("2^3^2").as_deref(), Ok("512"));
+    assert_eq!(eval("2 × -3").as_deref(), Ok("-6"));
+    assert_eq!(eval("10 - 4 - 3").as_deref(), Ok("3"));
+}
+
+#[test]
+fn functions_and_constants() {
+    assert_eq!(eval("sqrt(16) + 5!").as_deref(), Ok("124"));
+    assert_eq!(eval("sin 30").as_deref(), Ok("0.5"));
+    assert_eq!(eval("ln e").as_deref(), Ok("1"));
+}
+
+#[test]
+fn malformed_expressions() {
+    for text in ["(2 + 3", "2 + 3)", ")(", "2 +", "× 2", "", "2 3", "sqrt"] {
+        assert_eq!(eval(text), Err(CalcError::InvalidInput), "{:?}", text);
+    }
+    assert_eq!(eval("1 / 0"), Err(CalcError::DivisionByZero));
+    assert_eq!(eval("sqrt(-1)"), Err(CalcError::Domain));
+    assert_eq!(eval("1e10000"), Err(CalcError::Overflow));
+    assert_eq!(eval("10^10000"), Err(CalcError::Overflow));
+}
+
+#[test]
+fn arithmetics() {
+    let exact = context(Arithmetic::Rational);
+    assert_eq!(eval_with(exact, "1/3 * 3").as_deref(), Ok("1"));
+    assert_eq!(eval_with(exact, "1/3 + 1/6").as_deref(), Ok("1/2"));
+    let float = context(Arithmetic::Float);
+    // El modo rápido guarda diez decimales; el decimal, las cifras de la precisión
+    assert_eq!(eval_with(float, "1/3").as_deref(), Ok("0.3333333333"));
+    assert_eq!(eval("1/3"), Ok(format!("0.{}", "3".repeat(32))));
+    let mut short = Context::default();
+    short.precision = 5;
+    assert_eq!(eval_with(short, "2/3").as_deref(), Ok("0.66667"));
+}
+
+#[test]
+fn integers() {
+    let mut hex = context(Arithmetic::Integer);
+    hex.radix = Radix::Hex;
+    assert_eq!(eval_with(hex, "ff + 1").as_deref(), Ok("100"));
+    assert_eq!(eval_with(hex, "ff and not 0f").as_deref(), Ok("F0"));
+    assert_eq!(eval_with(hex, "1 lsh 4").as_deref(), Ok("10"));
+    let mut byte = context(Arithmetic::Integer);
+    byte.word = WordSize::Byte;
+    assert_eq!(eval_with(byte, "127 + 1").as_deref(), Ok("-128"));
+    assert_eq!(eval_with(byte, "7 ÷ 2").as_deref(), Ok("3"));
+    assert_eq!(eval_with(byte, "7 mod 2").as_deref(), Ok("1"));
+}
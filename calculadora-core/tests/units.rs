@@ -0,0 +1,75 @@
+// La tabla de unidades incluida y algunas conversiones conocidas
+
+use calculadora_core::units::{categories, parse, Unit};
+use calculadora_core::{Arithmetic, Context, Format, Radix};
+
+fn unit(category: &str, symbol: &str) -> &'static Unit {
+    let category = categories().iter().find(|c| c.name == category).unwrap();
+    category.units.iter().find(|u| u.symbol == symbol).unwrap()
+}
+
+// Convierte y devuelve el resultado como en la pantalla
+fn convert(context: Context, value: &str, category: &str, from: &str, to: &str) -> String {
+    let value = context.parse(value).unwrap();
+    let result = context.convert_unit(&value, unit(category, from), unit(category, to)).unwrap();
+    Format::default().display(&context.show(&context.format(&result)), Radix::Dec)
+}
+
+#[test]
+fn table_has_every_category() {
+    let names = categories().iter().map(|c| c.name.as_str()).collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        ["Longitud", "Masa", "Temperatura", "Volumen", "Área", "Velocidad", "Tiempo", "Datos", "Energía", "Presión", "Ángulo"]
+    );
+}
+
+#[test]
+fn every_unit_converts_back_to_itself() {
+    let mut context = Context::default();
+    context.arithmetic = Arithmetic::Rational;
+    let value = context.parse("7").unwrap();
+    for category in categories() {
+        let base = &category.units[0];
+        for unit in &category.units {
+            let there = context.convert_unit(&value, unit, base).unwrap();
+            let back = context.convert_unit(&there, base, unit).unwrap();
+            assert_eq!(back, value, "{} ({})", unit.name, category.name);
+        }
+    }
+}
+
+#[test]
+fn known_conversions() {
+    let decimal = Context::default();
+    let mut exact = Context::default();
+    exact.arithmetic = Arithmetic::Rational;
+    assert_eq!(convert(decimal, "1", "Longitud", "mi", "km"), "1.609344");
+    assert_eq!(convert(decimal, "12", "Longitud", "in", "ft"), "1");
+    assert_eq!(convert(exact, "100", "Temperatura", "°C", "°F"), "212");
+    assert_eq!(convert(exact, "-40", "Temperatura", "°F", "°C"), "-40");
+    assert_eq!(convert(decimal, "0", "Temperatura", "°C", "K"), "273.15");
+    assert_eq!(convert(decimal, "36", "Velocidad", "km/h", "m/s"), "10");
+    assert_eq!(convert(decimal, "1", "Datos", "MiB", "KiB"), "1024");
+    assert_eq!(convert(decimal, "1", "Datos", "B", "bit"), "8");
+    assert_eq!(convert(decimal, "1", "Energía", "kcal", "J"), "4184");
+    assert_eq!(convert(decimal, "1", "Presión", "atm", "Pa"), "101325");
+    assert_eq!(convert(decimal, "90", "Ángulo", "°", "grad"), "100");
+    assert_eq!(convert(decimal, "180", "Ángulo", "°", "rad"), "3.1415926535897932385");
+}
+
+#[test]
+fn malformed_tables_report_the_line() {
+    assert_eq!(parse("[Longitud]\nmetro | m | 1\npie | ft\n"), Err(3));
+    assert_eq!(parse("metro | m | 1\n"), Err(1));
+    assert_eq!(parse("# nada\n\n").map(|c| c.len()), Ok(0));
+    // Categorías sin unidades, en medio o al final
+    assert_eq!(parse("[Longitud]\n[Masa]\nkilogramo | kg | 1\n"), Err(1));
+    assert_eq!(parse("[Masa]\nkilogramo | kg | 1\n\n[Longitud]\n"), Err(4));
+    // Factores y desplazamientos que no se pueden calcular
+    assert_eq!(parse("[Masa]\nkilogramo | kg | 1\ngramo | g | 1/\n"), Err(3));
+    assert_eq!(parse("[Temperatura]\nkelvin | K | 1\ngrado Celsius | °C | 1 | 273,15x\n"), Err(3));
+    // Factores nulos, que harían dividir por cero
+    assert_eq!(parse("[Longitud]\nmetro | m | 1\npie | ft | 0\n"), Err(3));
+    assert_eq!(parse("[Longitud]\nmetro | m | 1\npie | ft | 1-1\n"), Err(3));
+}
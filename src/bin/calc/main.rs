@@ -0,0 +1,168 @@
+// Calculadora de línea de órdenes: evalúa la expresión de los argumentos o,
+// si no hay, una por línea de la entrada estándar; desde un terminal abre el
+// modo interactivo. Usa el mismo motor y el mismo formato de pantalla que la
+// interfaz gráfica.
+
+mod repl;
+
+use std::io::{self, BufRead, IsTerminal};
+use std::process::ExitCode;
+
+use calculadora_core::{evaluate, Arithmetic, Context, Format, Number, Radix};
+
+const USAGE: &str = "\
+Uso: calc [opciones] [expresión...]
+
+Sin expresión, lee una por línea de la entrada estándar o, si es un
+terminal, abre el modo interactivo (escribe :help dentro para ver las órdenes).
+
+Opciones:
+  -x, --exact          aritmética exacta con fracciones
+  -f, --float          aritmética rápida en coma flotante (f64)
+  -p, --precision N    cifras significativas del modo decimal (32)
+  -m, --mode M         aritmética: decimal, exact, float, o enteros en hex, dec, oct, bin
+  -w, --word W         tamaño de los enteros: byte, word, dword, qword (qword)
+  -g, --grouping       agrupa las cifras de los miles
+  -l, --locale es|en   formato español (1 234,5) o inglés (1,234.5)
+  -d, --decimal , | .  separador decimal
+  -n, --digits N       cifras antes de pasar a notación científica (20)
+  -e, --fe             siempre en notación científica (F-E)
+  -i, --interactive    abre el modo interactivo aunque no sea un terminal
+  -h, --help           muestra esta ayuda";
+
+struct Options {
+    context: Context,
+    format: Format,
+    interactive: bool,
+    expression: Vec<String>,
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        },
+        Err(message) => {
+            eprintln!("calc: {}\n\n{}", message, USAGE);
+            return ExitCode::from(2);
+        },
+    };
+
+    if !options.expression.is_empty() {
+        let line = options.expression.join(" ");
+        return if run(&line, &options) { ExitCode::SUCCESS } else { ExitCode::FAILURE };
+    }
+    if options.interactive || io::stdin().is_terminal() {
+        repl::run(options);
+        return ExitCode::SUCCESS;
+    }
+
+    // Con varias líneas se siguen evaluando las demás aunque una falle
+    let mut ok = true;
+    for line in io::stdin().lock().lines() {
+        let Ok(line) = line else {
+            eprintln!("calc: no se pudo leer la entrada estándar");
+            return ExitCode::FAILURE;
+        };
+        if !line.trim().is_empty() {
+            ok &= run(&line, &options);
+        }
+    }
+    if ok { ExitCode::SUCCESS } else { ExitCode::FAILURE }
+}
+
+// Evalúa e imprime una expresión; false si ha dado error
+fn run(line: &str, options: &Options) -> bool {
+    match evaluate(&options.format.input(line), &options.context) {
+        Ok(number) => {
+            println!("{}", show(&number, options));
+            true
+        },
+        Err(error) => {
+            eprintln!("calc: {}: {}", line.trim(), error);
+            false
+        },
+    }
+}
+
+// Como en la pantalla: los enteros en su base y sin notación científica
+fn show(number: &Number, options: &Options) -> String {
+    let Options { context, format, .. } = options;
+    let text = context.show(&context.format(number));
+    match context.arithmetic {
+        Arithmetic::Integer => Format { exponent: false, digits: 64, ..*format }.display(&text, context.radix),
+        _ => format.display(&text, Radix::Dec),
+    }
+}
+
+// None si se pidió la ayuda
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        context: Context::default(),
+        format: Format::default(),
+        interactive: false,
+        expression: Vec::new(),
+    };
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("falta el valor de {}", name));
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-x" | "--exact" => options.context.arithmetic = Arithmetic::Rational,
+            "-f" | "--float" => options.context.arithmetic = Arithmetic::Float,
+            "-p" | "--precision" => {
+                let precision = value(&arg)?;
+                options.context.precision = precision
+                    .parse()
+                    .ok()
+                    .filter(|p| *p > 0)
+                    .ok_or(format!("precisión no válida: {}", precision))?;
+                // Como :prec, que se vean todas las cifras pedidas
+                options.format.digits = options.format.digits.max(options.context.precision as usize);
+            },
+            "-m" | "--mode" => {
+                let mode = value(&arg)?;
+                (options.context.arithmetic, options.context.radix) =
+                    repl::mode_of(&mode).ok_or(format!("modo desconocido: {}", mode))?;
+            },
+            "-w" | "--word" => {
+                let word = value(&arg)?;
+                options.context.word = word.parse().map_err(|_| format!("tamaño de palabra desconocido: {}", word))?;
+            },
+            "-g" | "--grouping" => options.format.grouping = true,
+            "-l" | "--locale" => {
+                let format = match value(&arg)?.as_str() {
+                    "es" => Format::spanish(),
+                    "en" => Format::english(),
+                    other => return Err(format!("formato desconocido: {}", other)),
+                };
+                options.format = Format { exponent: options.format.exponent, digits: options.format.digits, ..format };
+            },
+            "-d" | "--decimal" => {
+                let decimal = match value(&arg)?.as_str() {
+                    "," => ',',
+                    "." => '.',
+                    other => return Err(format!("separador decimal no válido: {}", other)),
+                };
+                options.format = options.format.with_decimal(decimal);
+            },
+            "-n" | "--digits" => {
+                let digits = value(&arg)?;
+                options.format.digits = digits
+                    .parse()
+                    .ok()
+                    .filter(|d| *d > 0)
+                    .ok_or(format!("número de cifras no válido: {}", digits))?;
+            },
+            "-e" | "--fe" => options.format.exponent = true,
+            "-i" | "--interactive" => options.interactive = true,
+            // Un número negativo es parte de la expresión, no una opción
+            _ if arg.starts_with('-') && !arg[1..].starts_with(|c: char| c.is_ascii_digit() || c == '.' || c == '(') => {
+                return Err(format!("opción desconocida: {}", arg));
+            },
+            _ => options.expression.push(arg),
+        }
+    }
+    Ok(Some(options))
+}
@@ -0,0 +1,157 @@
+// Modo interactivo de calc, pensado para usarlo por SSH sin interfaz gráfica:
+// edición de línea, historial guardado en $XDG_STATE_HOME/rusticalculadora/history
+// (o ~/.local/state), la variable ans con el último resultado y órdenes como
+// :mode hex o :prec 50 para cambiar la aritmética sin salir.
+
+use std::fs;
+use std::path::PathBuf;
+
+use calculadora_core::{evaluate, Arithmetic, Number, Radix};
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
+
+use crate::{show, Options};
+
+const HELP: &str = "\
+Escribe una expresión, como 2 + 3 * 4, sqrt(2), sin 30, 5! o ans / 2.
+
+Órdenes:
+  :mode M     aritmética: decimal, exact, float, o enteros en hex, dec, oct, bin
+  :prec N     cifras significativas del modo decimal
+  :angle A    unidad de los ángulos: deg, rad, grad
+  :word W     tamaño de los enteros: byte, word, dword, qword
+  :help       muestra esta ayuda
+  :quit       sale (también Ctrl+D)";
+
+pub fn run(mut options: Options) {
+    let mut editor = match DefaultEditor::new() {
+        Ok(editor) => editor,
+        Err(error) => {
+            eprintln!("calc: no se pudo abrir el terminal: {}", error);
+            return;
+        },
+    };
+    let history = path();
+    if let Some(path) = &history {
+        // La primera vez todavía no existe
+        let _ = editor.load_history(path);
+    }
+
+    let mut ans = options.context.convert(&Number::Integer(0));
+    loop {
+        let line = match editor.readline(&prompt(&options)) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(error) => {
+                eprintln!("calc: {}", error);
+                break;
+            },
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let _ = editor.add_history_entry(line);
+
+        if let Some(command) = line.strip_prefix(':') {
+            let mut words = command.split_whitespace();
+            let name = words.next().unwrap_or_default();
+            if matches!(name, "q" | "quit" | "exit") {
+                break;
+            }
+            match command_of(name, words.next(), &mut options) {
+                Ok(()) => ans = options.context.convert(&ans),
+                Err(message) => eprintln!("calc: {}", message),
+            }
+            continue;
+        }
+
+        let context = &options.context;
+        let value = format!("({})", context.format(&ans));
+        match evaluate(&with_ans(&options.format.input(line), &value), context) {
+            Ok(number) => {
+                println!("{}", show(&number, &options));
+                ans = number;
+            },
+            Err(error) => eprintln!("calc: {}", error),
+        }
+    }
+
+    if let Some(path) = &history {
+        if let Some(dir) = path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        if let Err(error) = editor.save_history(path) {
+            eprintln!("calc: no se pudo guardar el historial: {}", error);
+        }
+    }
+}
+
+// Aplica una orden :nombre valor a las opciones
+fn command_of(name: &str, value: Option<&str>, options: &mut Options) -> Result<(), String> {
+    let context = &mut options.context;
+    match name {
+        "help" | "h" => println!("{}", HELP),
+        "mode" => {
+            let value = value.ok_or("uso: :mode decimal|exact|float|hex|dec|oct|bin")?;
+            (context.arithmetic, context.radix) = mode_of(value).ok_or(format!("modo desconocido: {}", value))?;
+        },
+        "prec" => {
+            let value = value.ok_or("uso: :prec N")?;
+            context.precision = value.parse().ok().filter(|p| *p > 0).ok_or(format!("precisión no válida: {}", value))?;
+            // Que se vean todas las cifras pedidas antes de pasar a notación científica
+            options.format.digits = options.format.digits.max(context.precision as usize);
+        },
+        "angle" => context.angle = value.and_then(|v| v.parse().ok()).ok_or("uso: :angle deg|rad|grad")?,
+        "word" => context.word = value.and_then(|v| v.parse().ok()).ok_or("uso: :word byte|word|dword|qword")?,
+        _ => return Err(format!("orden desconocida: :{} (prueba :help)", name)),
+    }
+    Ok(())
+}
+
+// El indicador muestra el modo si no es el decimal por defecto
+fn prompt(options: &Options) -> String {
+    let context = &options.context;
+    match context.arithmetic {
+        Arithmetic::Decimal => "> ".to_string(),
+        Arithmetic::Rational => "exact> ".to_string(),
+        Arithmetic::Integer => format!("{}> ", context.radix.name()),
+        arithmetic => format!("{}> ", arithmetic.name()),
+    }
+}
+
+// Los modos de :mode y -m: decimal, exact (la racional), float o la base de los enteros
+pub(crate) fn mode_of(name: &str) -> Option<(Arithmetic, Radix)> {
+    match name {
+        "exact" => Some((Arithmetic::Rational, Radix::Dec)),
+        "decimal" | "float" => Some((name.parse().ok()?, Radix::Dec)),
+        _ => Some((Arithmetic::Integer, name.parse().ok()?)),
+    }
+}
+
+// Sustituye la palabra ans (no parte de otra) por el último resultado
+fn with_ans(line: &str, value: &str) -> String {
+    let mut result = String::new();
+    let mut word = String::new();
+    for c in line.chars().chain(Some('\0')) {
+        if c.is_alphanumeric() {
+            word.push(c);
+            continue;
+        }
+        result.push_str(if word.eq_ignore_ascii_case("ans") { value } else { &word });
+        word.clear();
+        if c != '\0' {
+            result.push(c);
+        }
+    }
+    result
+}
+
+fn path() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_STATE_HOME").map(PathBuf::from) {
+        Some(dir) if dir.is_absolute() => dir,
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".local").join("state"),
+    };
+    Some(base.join("rusticalculadora").join("history"))
+}
@@ -1,26 +1,117 @@
-use iced::widget::{button, Button, Column, Row, Text, container};
-use iced::{Alignment, Command, Element, Length, Sandbox, Theme};
+use iced::widget::{button, Button, Checkbox, Column, PickList, Radio, Row, Scrollable, Text, container, text_input};
+use iced::keyboard::{self, KeyCode};
+use iced::{clipboard, event, executor, subscription, window, Application, Command, Element, Event, Length, Size, Subscription, Theme};
 use iced::alignment::Horizontal;
 use iced::theme;
-use iced::theme::Button as ThemeButton;
 use iced::Background;
 use iced::Color;
 
-const LEN: u32 = 450;
+use calculadora_core::units::{self, Unit};
+use calculadora_core::{Angle, Arithmetic, Constant, Context, Engine, Entry, Format, Function, Input, Memory, Operator, Radix, Register, Special, Stat, WordSize};
+
+use crate::config::Config;
 
 #[derive(Debug, Clone)]
 pub enum Message {
     Num(char),
-    Sign(char),
+    Operator(Operator),
     Ans,
     Dot,
     Neg,
     Clear,
     ClearEnd,
     Backspace,
-    Memory(char),  // MC, MR, MS, M+
-    Special(char), // sqrt, 1/x, %
+    Memory(Memory),
+    Register(usize, Memory), // lo mismo sobre un registro del panel de memoria
+    RenameRegister(usize, String),
+    Special(Special),
+    Open,          // ( y ), modo científico
+    Close,
+    Function(Function),
+    Constant(Constant),
+    Mode(Mode),
+    Arithmetic(Arithmetic),
+    Fractions(bool),
+    Angle(Angle),
+    Inverse(bool),
+    Hyperbolic(bool),
+    Radix(Radix),
+    WordSize(WordSize),
+    Dat,
+    Stat(Stat),        // Ave, Sum, s y demás resúmenes
+    LoadData(usize),   // lleva un dato a la pantalla
+    RemoveData(usize),
+    ClearData,
+    History,                 // muestra u oculta la cinta
+    MemoryPanel,
+    RecallResult(usize),
+    RecallExpression(usize),
+    ClearHistory,
+    Undo,
+    Redo,
+    Menu(Menu),            // abre o cierra un menú de la barra
+    Grouping(bool),
+    Format(Format),        // separadores y notación de la pantalla
+    Exp,
+    Dialog(Option<Dialog>),
+    Copy,
+    Paste,
+    Pasted(Option<String>), // lo leído del portapapeles
+    Category(usize),        // categoría de la vista de conversión
+    FromUnit(usize),
+    ToUnit(usize),
+    SwapUnits,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Standard,
+    Scientific,   // funciones, paréntesis y evaluación con precedencia
+    Programmer,   // enteros en complemento a dos, bases y operaciones de bits
+    Statistics,   // lista de datos y sus resúmenes, como el cuadro "Sta" de XP
+    Converter,    // conversión de unidades del número en pantalla
+}
+
+// Categoría y unidades elegidas en la vista de conversión, como posiciones
+// en la tabla de unidades
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Conversion {
+    pub category: usize,
+    pub from: usize,
+    pub to: usize,
+}
+
+impl Default for Conversion {
+    fn default() -> Self {
+        Self { category: 0, from: 0, to: 1 }
     }
+}
+
+// Opción de una lista desplegable: la posición en la tabla y el texto que se ve
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Choice {
+    index: usize,
+    label: String,
+}
+
+impl std::fmt::Display for Choice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.label)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Menu {
+    View,
+    Edit,
+    Help,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dialog {
+    About,
+    Keys, // atajos de teclado
+}
 
 #[derive(Debug, Clone, Copy)]
 pub enum ButtonType {
@@ -33,6 +124,7 @@ pub enum ButtonType {
 }
 
 pub struct CustomButtonStyle {
+    #[allow(dead_code)]
     pub button_type: ButtonType,
 }
 
@@ -82,66 +174,78 @@ impl button::StyleSheet for CustomButtonStyle {
 
 
 pub struct Calculator {
-    left: String,
-    right: String,
-    sign: String,
-    shadow: bool,
-    memory: f64,
+    engine: Engine,
+    mode: Mode,
+    inverse: bool,
+    hyperbolic: bool,
+    arithmetic: Arithmetic, // la que se recupera al salir del modo programador
+    history: bool,
+    memory_panel: bool,
+    format: Format,
+    conversion: Conversion,
+    menu: Option<Menu>,
+    dialog: Option<Dialog>,
+    undo: Vec<Engine>, // estados anteriores del motor, el último es el más reciente
+    redo: Vec<Engine>,
+    saved: Option<Stamp>, // lo último escrito en el fichero de configuración
+}
+
+// Lo que decide si hay que volver a escribir la configuración, sin convertir la
+// memoria ni el historial en cada tecla. El historial solo crece, se borra o
+// vuelve a un estado anterior, así que basta con su longitud y su última entrada.
+#[derive(Debug, PartialEq)]
+struct Stamp {
+    mode: Mode,
+    context: Context,
+    arithmetic: Arithmetic,
+    format: Format,
+    conversion: Conversion,
+    memory: Vec<Register>,
+    history: usize,
+    last: Option<Entry>,
 }
 
 impl Default for Calculator {
     fn default() -> Self {
         Self {
-            left: String::from("0"),
-            right: String::new(),
-            sign: String::new(),
-            shadow: false,
-            memory: 0.0,
+            engine: Engine::new(),
+            mode: Mode::Standard,
+            inverse: false,
+            hyperbolic: false,
+            arithmetic: Arithmetic::Decimal,
+            history: false,
+            memory_panel: false,
+            format: Format::default(),
+            conversion: Conversion::default(),
+            menu: None,
+            dialog: None,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            saved: None,
         }
     }
 }
 
-impl Calculator {
-    fn calculate(&mut self) -> Result<(), &'static str> {
-        self.shadow = true;
-        self.left = match self.sign.as_str() {
-            "+" => {
-                format!("{:.10}", self.left.parse::<f64>().unwrap() + self.right.parse::<f64>().unwrap())
-            },
-            "-" => {
-                format!("{:.10}", self.left.parse::<f64>().unwrap() - self.right.parse::<f64>().unwrap())
-            },
-            "×" => {
-                format!("{:.10}", self.left.parse::<f64>().unwrap() * self.right.parse::<f64>().unwrap())
-            },
-            "÷" => {
-                let r = self.right.parse::<f64>().unwrap();
-                if r == 0.0 {
-                    "Error: División por cero".into()
-                } else {
-                    format!("{:.10}", self.left.parse::<f64>().unwrap() / r)
-                }
-            },
-            _ => unreachable!()
-        };
-        self.cleanup_number();
-        Ok(())
-    }
+fn menu_button(label: &str, message: Message) -> Button<'_, Message> {
+    Button::new(Text::new(label).size(11))
+        .padding(0)
+        .style(theme::Button::Text)
+        .on_press(message)
+}
 
-    fn cleanup_number(&mut self) {
-        while self.left.ends_with('0') && self.left.contains('.') {
-            self.left.pop();
-        }
-        if self.left.ends_with('.') {
-            self.left.pop();
-        }
-    }
+fn key(label: &str, message: Message, button_type: ButtonType) -> Button<'_, Message> {
+    key_enabled(label, message, button_type, true)
+}
 
-    fn clear(&mut self, c: char) {
-        self.left = c.into();
-        self.sign.clear();
-        self.right.clear();
-        self.shadow = false;
+// Botón que solo responde si `enabled`, como las cifras que no existen en la base activa
+fn key_enabled(label: &str, message: Message, button_type: ButtonType, enabled: bool) -> Button<'_, Message> {
+    let button = Button::new(Text::new(label).size(12))
+        .width(Length::Fixed(35.0))
+        .style(theme::Button::Custom(Box::new(CustomButtonStyle { button_type })));
+    if enabled {
+        button.on_press(message)
+    } else {
+        button
     }
 }
 
@@ -161,351 +265,970 @@ impl container::StyleSheet for ContainerStyle {
     }
 }
 
-impl Sandbox for Calculator {
+impl Application for Calculator {
+    type Executor = executor::Default;
     type Message = Message;
+    type Theme = Theme;
+    type Flags = ();
 
-    fn new() -> Self {
-        Self::default()
+    fn new(_flags: ()) -> (Self, Command<Message>) {
+        let mut calculator = Self::default();
+        let Some(config) = Config::load() else {
+            return (calculator, Command::none());
+        };
+        calculator.format = config.format;
+        calculator.conversion = config.conversion;
+        calculator.engine.set_context(config.context);
+        calculator.engine.restore(config.memory, config.history);
+        let command = calculator.handle(Message::Mode(config.mode));
+        calculator.saved = Some(calculator.stamp());
+        (calculator, command)
     }
 
     fn title(&self) -> String {
         String::from("RustiCalculadora")
     }
 
-    fn update(&mut self, message: Message) -> () {
-        match message {
-            Message::Num(n) => {
-                if self.sign.is_empty() {
-                    if &self.left == "0" {
-                        self.left = n.to_string();
-                    } else {
-                        self.left.push(n);
-                    }
-                } else if !self.left.is_empty() && self.shadow {
-                    self.clear(n);
-                } else if &self.right == "0" {
-                    self.right = n.to_string();
-                } else {
-                    self.right.push(n);
-                }
-            },
-            Message::Sign(s) => {
-                if self.sign.is_empty() {
-                    self.sign.push(s);
-                } else {
-                    if !self.shadow && !self.right.is_empty() {
-                        let _ = self.calculate();
-                    }
-                    self.sign = s.to_string();
-                    self.right.clear();
-                    self.shadow = false;
+    fn update(&mut self, message: Message) -> Command<Message> {
+        // Cualquier acción cierra el menú abierto
+        if !matches!(message, Message::Menu(_)) {
+            self.menu = None;
+        }
+        let command = match message {
+            // Deshacer también cambia la memoria y el historial que se guardan
+            Message::Undo => {
+                if let Some(engine) = self.undo.pop() {
+                    self.redo.push(std::mem::replace(&mut self.engine, engine));
                 }
+                Command::none()
             },
-            Message::Ans => {
-                if !self.sign.is_empty() && !self.left.is_empty() && !self.right.is_empty() {
-                    let _ = self.calculate();
+            Message::Redo => {
+                if let Some(engine) = self.redo.pop() {
+                    self.undo.push(std::mem::replace(&mut self.engine, engine));
                 }
+                Command::none()
             },
-            Message::Clear => self.clear('0'),
-            Message::ClearEnd => {
-                if self.right.is_empty() {
-                    self.left = "0".to_string();
-                    self.sign.clear();
-                    self.shadow = false;
-                } else {
-                    self.right.clear();
+            _ => {
+                // Cambiar de vista cambia el modo de evaluación; lo anterior ya no se puede deshacer
+                if matches!(message, Message::Mode(_)) {
+                    self.undo.clear();
+                    self.redo.clear();
                 }
-            },
-            Message::Backspace => {
-                if self.sign.is_empty() {
-                    self.left.pop();
-                    if self.left.is_empty() {
-                        self.left.push('0');
+                let before = self.engine.clone();
+                let command = self.handle(message);
+                if self.engine != before {
+                    if self.undo.len() == UNDO_LIMIT {
+                        self.undo.remove(0);
                     }
-                } else {
-                    self.right.pop();
+                    self.undo.push(before);
+                    self.redo.clear();
                 }
+                command
             },
-            Message::Dot => {
-                if self.sign.is_empty() && !self.left.contains('.') {
-                    self.left.push('.');
-                } else if !self.sign.is_empty() && !self.right.contains('.') {
-                    if self.right.is_empty() {
-                        self.right.push('0');
-                    }
-                    self.right.push('.');
+        };
+        self.save();
+        command
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        subscription::events_with(keyboard_message)
+    }
+
+    fn view(&self) -> Element<'_, Message> {
+        let display_text = match self.engine.error() {
+            Some(error) => error.to_string(),
+            None if self.engine.is_typing() => self.display_format().typing(&self.engine.display(), self.engine.context().radix),
+            None => self.display_format().display(&self.engine.display(), self.engine.context().radix),
+        };
+
+        let menu_row = Row::new()
+            .push(menu_button("Ver", Message::Menu(Menu::View)))
+            .push(menu_button("Edición", Message::Menu(Menu::Edit)))
+            .push(menu_button("Ayuda", Message::Menu(Menu::Help)))
+            .spacing(5)
+            .padding(2);
+
+        let content = Column::new()
+            .spacing(1)
+            .padding(1)
+            .push(menu_row);
+
+        // El menú abierto o un diálogo ocupan el sitio del teclado hasta cerrarlos
+        let overlay = match (self.menu, self.dialog) {
+            (Some(menu), _) => Some(self.menu_items(menu)),
+            (None, Some(dialog)) => Some(dialog_box(dialog)),
+            (None, None) => None,
+        };
+        if let Some(overlay) = overlay {
+            return container(content.push(overlay))
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .style(theme::Container::Custom(Box::new(MainContainerStyle)))
+                .into();
+        }
+
+        let content = content
+            .push(
+                container(
+                    Text::new(display_text)
+                        .size(16)
+                        .width(Length::Fill)
+                        .horizontal_alignment(Horizontal::Right)
+                )
+                .padding(3)
+                .style(theme::Container::Custom(Box::new(ContainerStyle)))
+                .width(Length::Fill)
+                .height(Length::Fixed(25.0))
+            );
+
+        let content = match self.mode {
+            Mode::Standard => content.push(self.keypad()),
+            Mode::Scientific => content
+                .push(self.scientific_options())
+                .push(
+                    Row::new()
+                        .spacing(5)
+                        .push(self.scientific_keys())
+                        .push(self.keypad())
+                ),
+            Mode::Programmer => content
+                .push(self.programmer_options())
+                .push(
+                    Row::new()
+                        .spacing(5)
+                        .push(self.programmer_keys())
+                        .push(self.keypad())
+                ),
+            Mode::Statistics => content.push(
+                Row::new()
+                    .spacing(5)
+                    .push(self.data_box())
+                    .push(self.statistics_keys())
+                    .push(self.keypad())
+            ),
+            Mode::Converter => content.push(
+                Row::new()
+                    .spacing(5)
+                    .push(self.converter_box())
+                    .push(self.keypad())
+            ),
+        };
+
+        let mut content = Row::new().spacing(5).push(content);
+        if self.history {
+            content = content.push(self.history_panel());
+        }
+        if self.memory_panel {
+            content = content.push(self.memory_panel());
+        }
+
+        container(content)
+            .style(theme::Container::Custom(Box::new(MainContainerStyle)))
+            .into()
+    }
+}
+
+impl Calculator {
+    // Lo que se conserva entre sesiones. En modo programador la memoria y el
+    // historial se pasan a la aritmética normal, que es la que se guarda.
+    fn config(&self) -> Config {
+        let mut context = self.engine.context();
+        if self.mode == Mode::Programmer {
+            context.arithmetic = self.arithmetic;
+        }
+        let mut engine = self.engine.clone();
+        engine.set_context(context);
+        Config {
+            mode: self.mode,
+            context,
+            format: self.format,
+            conversion: self.conversion,
+            memory: engine.registers().to_vec(),
+            history: engine.entries().to_vec(),
+        }
+    }
+
+    // Escribe la configuración si ha cambiado algo de lo que se guarda. Si no
+    // se puede escribir se sigue igual; solo se pierde al cerrar.
+    fn save(&mut self) {
+        let stamp = self.stamp();
+        if self.saved.as_ref() != Some(&stamp) {
+            let _ = self.config().save();
+            self.saved = Some(stamp);
+        }
+    }
+
+    fn stamp(&self) -> Stamp {
+        let history = self.engine.entries();
+        Stamp {
+            mode: self.mode,
+            context: self.engine.context(),
+            arithmetic: self.arithmetic,
+            format: self.format,
+            conversion: self.conversion,
+            memory: self.engine.registers().to_vec(),
+            history: history.len(),
+            last: history.last().cloned(),
+        }
+    }
+
+    // El modo programador no usa notación científica: un entero siempre cabe
+    fn change_context(&mut self, change: impl FnOnce(&mut Context)) {
+        let mut context = self.engine.context();
+        change(&mut context);
+        self.engine.set_context(context);
+    }
+
+    fn display_format(&self) -> Format {
+        match self.mode {
+            Mode::Programmer => Format { exponent: false, digits: 64, ..self.format },
+            _ => self.format,
+        }
+    }
+
+    fn menu_items(&self, menu: Menu) -> Element<'_, Message> {
+        // Marca de la opción activa, como las de los menús de XP
+        let check = |label: &str, on: bool| format!("{} {}", if on { "✓" } else { "   " }, label);
+        let item = |label: String, message: Message| {
+            Button::new(Text::new(label).size(11))
+                .padding(0)
+                .style(theme::Button::Text)
+                .on_press(message)
+        };
+        let context = self.engine.context();
+        let mut items = Column::new().spacing(4).padding(4);
+        match menu {
+            Menu::View => {
+                for (label, mode) in [
+                    ("Estándar", Mode::Standard),
+                    ("Científica", Mode::Scientific),
+                    ("Programador", Mode::Programmer),
+                    ("Estadística", Mode::Statistics),
+                    ("Conversión", Mode::Converter),
+                ] {
+                    items = items.push(item(check(label, self.mode == mode), Message::Mode(mode)));
                 }
-            },
-            Message::Neg => {
-                if self.sign.is_empty() {
-                    if &self.left != "0" && &self.left != "0." {
-                        if !self.left.starts_with('-') {
-                            self.left.insert(0, '-');
-                        } else {
-                            self.left.remove(0);
-                        }
-                    }
-                } else if !self.sign.is_empty() && self.shadow {
-                    if &self.left != "0" {
-                        if !self.left.starts_with('-') {
-                            self.left.insert(0, '-');
-                        } else {
-                            self.left.remove(0);
-                        }
+                let format = self.format;
+                let comma = format.decimal == ',';
+                let same = |preset: Format| (preset.decimal, preset.thousands) == (format.decimal, format.thousands);
+                items = items
+                    .push(item(check("Agrupación de dígitos", format.grouping), Message::Grouping(!format.grouping)))
+                    .push(item(check("Coma decimal", comma), Message::Format(format.with_decimal(if comma { '.' } else { ',' }))))
+                    .push(item(check("Formato español (1 234,5)", same(Format::spanish())), Message::Format(Format { exponent: format.exponent, digits: format.digits, ..Format::spanish() })))
+                    .push(item(check("Formato inglés (1,234.5)", same(Format::english())), Message::Format(Format { exponent: format.exponent, digits: format.digits, ..Format::english() })));
+                // Límite de cifras antes de pasar a notación científica
+                let digits = DIGIT_LIMITS.iter().copied().find(|d| *d > format.digits).unwrap_or(DIGIT_LIMITS[0]);
+                items = items.push(item(format!("    Cifras en pantalla: {}", format.digits), Message::Format(Format { digits, ..format })));
+                // Aritmética exacta con fracciones, que se pueden ver como tales o en decimal.
+                // En modo programador solo hay enteros y no se ofrece.
+                if self.mode != Mode::Programmer {
+                    let exact = context.arithmetic == Arithmetic::Rational;
+                    let arithmetic = if exact { Arithmetic::Decimal } else { Arithmetic::Rational };
+                    items = items.push(item(check("Aritmética exacta", exact), Message::Arithmetic(arithmetic)));
+                    if exact {
+                        items = items.push(item(check("Fracciones", context.fractions), Message::Fractions(!context.fractions)));
                     }
                 }
             },
-            Message::Memory(m) => {
-                match m {
-                    'C' => self.memory = 0.0, // MC
-                    'R' => self.left = self.memory.to_string(), // MR
-                    'S' => self.memory = self.left.parse().unwrap_or(0.0), // MS
-                    '+' => self.memory += self.left.parse::<f64>().unwrap_or(0.0), // M+
-                    _ => {}
+            Menu::Edit => {
+                items = items
+                    .push(item("Copiar (Ctrl+C)".to_string(), Message::Copy))
+                    .push(item("Pegar (Ctrl+V)".to_string(), Message::Paste))
+                    .push(item("Deshacer (Ctrl+Z)".to_string(), Message::Undo))
+                    .push(item("Rehacer (Ctrl+Y)".to_string(), Message::Redo))
+                    .push(item(check("Historial", self.history), Message::History))
+                    .push(item(check("Memoria", self.memory_panel), Message::MemoryPanel));
+            },
+            Menu::Help => {
+                items = items
+                    .push(item("Teclado".to_string(), Message::Dialog(Some(Dialog::Keys))))
+                    .push(item("Acerca de RustiCalculadora".to_string(), Message::Dialog(Some(Dialog::About))));
+            },
+        }
+        items.into()
+    }
+
+    // Aplica un mensaje sin pasar por la pila de deshacer
+    fn handle(&mut self, message: Message) -> Command<Message> {
+        let input = match message {
+            Message::Num(n) => Input::Num(n),
+            Message::Operator(operator) => Input::Operator(operator),
+            Message::Ans => Input::Ans,
+            Message::Dot => Input::Dot,
+            Message::Neg => Input::Neg,
+            Message::Clear => Input::Clear,
+            Message::ClearEnd => Input::ClearEnd,
+            Message::Backspace => Input::Backspace,
+            Message::Memory(m) => Input::Memory(m),
+            Message::Register(index, m) => Input::Register(index, m),
+            Message::Special(s) => Input::Special(s),
+            Message::Open => Input::Open,
+            Message::Close => Input::Close,
+            Message::Function(f) => {
+                // Como en XP, Inv y Hyp se desmarcan tras usarlos
+                self.inverse = false;
+                self.hyperbolic = false;
+                Input::Function(f)
+            },
+            Message::Constant(c) => Input::Constant(c),
+            Message::Dat => Input::Dat,
+            Message::Stat(s) => Input::Stat(s),
+            Message::Exp => Input::Exp,
+            Message::Mode(mode) => {
+                if mode == Mode::Programmer && self.mode != Mode::Programmer {
+                    self.arithmetic = self.engine.context().arithmetic;
+                    self.change_context(|context| context.arithmetic = Arithmetic::Integer);
+                } else if mode != Mode::Programmer && self.mode == Mode::Programmer {
+                    let arithmetic = self.arithmetic;
+                    self.change_context(|context| context.arithmetic = arithmetic);
                 }
+                self.mode = mode;
+                self.engine.set_precedence(mode == Mode::Scientific);
+                return self.resize();
             },
-            Message::Special(s) => {
-                match s {
-                    's' => { // sqrt
-                        let num = self.left.parse::<f64>().unwrap_or(0.0);
-                        if num >= 0.0 {
-                            self.left = num.sqrt().to_string();
-                        }
-                    },
-                    'i' => { // 1/x
-                        let num = self.left.parse::<f64>().unwrap_or(0.0);
-                        if num != 0.0 {
-                            self.left = (1.0/num).to_string();
-                        }
-                    },
-                    '%' => {
-                        let num = self.left.parse::<f64>().unwrap_or(0.0);
-                        self.left = (num/100.0).to_string();
+            Message::Arithmetic(arithmetic) => {
+                self.change_context(|context| context.arithmetic = arithmetic);
+                return Command::none();
+            },
+            Message::Fractions(fractions) => {
+                self.change_context(|context| context.fractions = fractions);
+                return Command::none();
+            },
+            Message::Angle(angle) => {
+                self.change_context(|context| context.angle = angle);
+                return Command::none();
+            },
+            Message::Inverse(inverse) => {
+                self.inverse = inverse;
+                return Command::none();
+            },
+            Message::Hyperbolic(hyperbolic) => {
+                self.hyperbolic = hyperbolic;
+                return Command::none();
+            },
+            Message::Radix(radix) => {
+                self.change_context(|context| context.radix = radix);
+                return Command::none();
+            },
+            Message::WordSize(word) => {
+                self.change_context(|context| context.word = word);
+                return Command::none();
+            },
+            Message::LoadData(index) => {
+                self.engine.load_data(index);
+                return Command::none();
+            },
+            Message::RemoveData(index) => {
+                self.engine.remove_data(index);
+                return Command::none();
+            },
+            Message::RenameRegister(index, name) => {
+                self.engine.rename_register(index, &name);
+                return Command::none();
+            },
+            Message::ClearData => {
+                self.engine.clear_data();
+                return Command::none();
+            },
+            Message::History => {
+                self.history = !self.history;
+                return self.resize();
+            },
+            Message::MemoryPanel => {
+                self.memory_panel = !self.memory_panel;
+                return self.resize();
+            },
+            Message::RecallResult(index) => {
+                self.engine.recall_result(index);
+                return Command::none();
+            },
+            Message::RecallExpression(index) => {
+                self.engine.recall_expression(index);
+                return Command::none();
+            },
+            Message::ClearHistory => {
+                self.engine.clear_history();
+                return Command::none();
+            },
+            Message::Menu(menu) => {
+                self.menu = if self.menu == Some(menu) { None } else { Some(menu) };
+                return Command::none();
+            },
+            Message::Grouping(grouping) => {
+                self.format.grouping = grouping;
+                return Command::none();
+            },
+            Message::Format(format) => {
+                self.format = format;
+                return Command::none();
+            },
+            Message::Dialog(dialog) => {
+                self.dialog = dialog;
+                return Command::none();
+            },
+            Message::Copy => {
+                return match self.engine.error() {
+                    Some(_) => Command::none(),
+                    // Con el separador decimal elegido, pero sin agrupar
+                    None => {
+                        let format = Format { grouping: false, ..self.display_format() };
+                        clipboard::write(format.display(&self.engine.value(), self.engine.context().radix))
                     },
-                    _ => {}
+                };
+            },
+            Message::Paste => {
+                return clipboard::read(Message::Pasted);
+            },
+            Message::Pasted(text) => {
+                if let Some(text) = text {
+                    self.engine.paste(&self.format.input(&text));
                 }
+                return Command::none();
+            },
+            Message::Category(category) => {
+                let units = units::categories()[category].units.len();
+                self.conversion = Conversion { category, from: 0, to: 1.min(units - 1) };
+                return Command::none();
+            },
+            Message::FromUnit(from) => {
+                self.conversion.from = from;
+                return Command::none();
+            },
+            Message::ToUnit(to) => {
+                self.conversion.to = to;
+                return Command::none();
+            },
+            Message::SwapUnits => {
+                let Conversion { from, to, .. } = self.conversion;
+                self.conversion = Conversion { from: to, to: from, ..self.conversion };
+                return Command::none();
+            },
+            Message::Undo | Message::Redo => return Command::none(),
+        };
+        self.engine.press(input);
+        Command::none()
+    }
+
+    fn resize(&self) -> Command<Message> {
+        let (mut width, height) = window_size(self.mode);
+        for open in [self.history, self.memory_panel] {
+            if open {
+                width += PANEL_WIDTH;
             }
         }
+        window::resize(Size::new(width, height))
     }
-    fn view(&self) -> Element<Message> {
-        let display_text = if self.shadow {
-            self.left.clone()
-        } else {
-            format!("{} {} {}", self.left, self.sign, self.right)
+
+    // Cinta de operaciones: la expresión recupera la operación entera y el
+    // resultado solo el número
+    fn history_panel(&self) -> Element<'_, Message> {
+        let history = self.engine.history();
+        let radix = self.engine.context().radix;
+        let header = Row::new()
+            .push(Text::new("Historial").size(11).width(Length::Fill))
+            .push(menu_button("Borrar", Message::ClearHistory));
+        let mut entries = Column::new().spacing(2);
+        for (index, (expression, result)) in history.into_iter().enumerate().rev() {
+            entries = entries.push(
+                Column::new()
+                    .push(
+                        Button::new(Text::new(format!("{} =", self.display_format().display(&expression, radix))).size(10))
+                            .padding(0)
+                            .style(theme::Button::Text)
+                            .on_press(Message::RecallExpression(index))
+                    )
+                    .push(
+                        Button::new(Text::new(self.display_format().display(&result, radix)).size(12))
+                            .padding(0)
+                            .width(Length::Fill)
+                            .style(theme::Button::Text)
+                            .on_press(Message::RecallResult(index))
+                    )
+            );
+        }
+        container(Column::new().spacing(2).push(header).push(Scrollable::new(entries)))
+            .padding(3)
+            .style(theme::Container::Custom(Box::new(ContainerStyle)))
+            .width(Length::Fixed(PANEL_WIDTH as f32 - 10.0))
+            .height(Length::Fill)
+            .into()
+    }
+
+    // Registros de memoria, cada uno con su nombre y sus propias teclas; MC
+    // quita los extra y deja a cero el principal
+    fn memory_panel(&self) -> Element<'_, Message> {
+        let memory = self.engine.memory();
+        let radix = self.engine.context().radix;
+        let header = Row::new()
+            .push(Text::new("Memoria").size(11).width(Length::Fill))
+            .push(menu_button("MS nuevo", Message::Register(memory.len(), Memory::Store)));
+        let mut registers = Column::new().spacing(2);
+        for (index, (name, value)) in memory.into_iter().enumerate() {
+            let mut keys = Row::new().spacing(4);
+            for (label, m) in [
+                ("MR", Memory::Recall),
+                ("MS", Memory::Store),
+                ("M+", Memory::Add),
+                ("M-", Memory::Subtract),
+                ("MC", Memory::Clear),
+            ] {
+                keys = keys.push(menu_button(label, Message::Register(index, m)));
+            }
+            registers = registers.push(
+                Column::new()
+                    .push(
+                        Row::new()
+                            .spacing(4)
+                            .push(
+                                text_input("Nombre", &name)
+                                    .on_input(move |name| Message::RenameRegister(index, name))
+                                    .size(11)
+                                    .padding(1)
+                                    .width(Length::Fixed(60.0))
+                            )
+                            .push(Text::new(self.display_format().display(&value, radix)).size(12))
+                    )
+                    .push(keys)
+            );
+        }
+        container(Column::new().spacing(2).push(header).push(Scrollable::new(registers)))
+            .padding(3)
+            .style(theme::Container::Custom(Box::new(ContainerStyle)))
+            .width(Length::Fixed(PANEL_WIDTH as f32 - 10.0))
+            .height(Length::Fill)
+            .into()
+    }
+
+    // Unidad de ángulo e Inv/Hyp, como la fila superior de la vista científica de XP
+    fn scientific_options(&self) -> Row<'_, Message> {
+        let angle = self.engine.context().angle;
+        let mut row = Row::new().spacing(8).padding(2);
+        for (label, value) in [("Deg", Angle::Degrees), ("Rad", Angle::Radians), ("Grad", Angle::Gradians)] {
+            row = row.push(
+                Radio::new(label, value, Some(angle), Message::Angle)
+                    .size(11)
+                    .spacing(3)
+                    .text_size(11)
+            );
+        }
+        row.push(Checkbox::new("Inv", self.inverse, Message::Inverse).size(11).spacing(3).text_size(11))
+            .push(Checkbox::new("Hyp", self.hyperbolic, Message::Hyperbolic).size(11).spacing(3).text_size(11))
+    }
+
+    fn scientific_keys(&self) -> Column<'_, Message> {
+        let trig = |f: Function| Message::Function(self.trig(f));
+        Column::new()
+            .spacing(1)
+            .push(
+                Row::new()
+                    .spacing(1)
+                    .push(key("sin", trig(Function::Sin), ButtonType::Special))
+                    .push(key("cos", trig(Function::Cos), ButtonType::Special))
+                    .push(key("tan", trig(Function::Tan), ButtonType::Special))
+            )
+            .push(
+                Row::new()
+                    .spacing(1)
+                    .push(key("ln", Message::Function(Function::Ln), ButtonType::Special))
+                    .push(key("log", Message::Function(Function::Log), ButtonType::Special))
+                    .push(key("eˣ", Message::Function(Function::Exp), ButtonType::Special))
+            )
+            .push(
+                Row::new()
+                    .spacing(1)
+                    .push(key("x²", Message::Function(Function::Square), ButtonType::Special))
+                    .push(key("x³", Message::Function(Function::Cube), ButtonType::Special))
+                    .push(key("xʸ", Message::Operator(Operator::Power), ButtonType::Operation))
+            )
+            .push(
+                Row::new()
+                    .spacing(1)
+                    .push(key("n!", Message::Function(Function::Factorial), ButtonType::Special))
+                    .push(key("ʸ√x", Message::Operator(Operator::Root), ButtonType::Operation))
+                    .push(key("π", Message::Constant(Constant::Pi), ButtonType::Number))
+            )
+            .push(
+                Row::new()
+                    .spacing(1)
+                    .push(key("(", Message::Open, ButtonType::Special))
+                    .push(key(")", Message::Close, ButtonType::Special))
+                    .push(key("e", Message::Constant(Constant::E), ButtonType::Number))
+            )
+            .push(
+                Row::new()
+                    .spacing(1)
+                    .push(key("F-E", Message::Format(Format { exponent: !self.format.exponent, ..self.format }), ButtonType::Special))
+                    .push(key("Exp", Message::Exp, ButtonType::Special))
+            )
+    }
+
+    // Base y tamaño de palabra, como los botones de radio de XP en modo Hex/Dec/Oct/Bin
+    fn programmer_options(&self) -> Column<'_, Message> {
+        let context = self.engine.context();
+        let mut radix_row = Row::new().spacing(8).padding(2);
+        for (label, value) in [("Hex", Radix::Hex), ("Dec", Radix::Dec), ("Oct", Radix::Oct), ("Bin", Radix::Bin)] {
+            radix_row = radix_row.push(
+                Radio::new(label, value, Some(context.radix), Message::Radix)
+                    .size(11)
+                    .spacing(3)
+                    .text_size(11)
+            );
+        }
+        let mut word_row = Row::new().spacing(8).padding(2);
+        for (label, value) in [("Qword", WordSize::QWord), ("Dword", WordSize::DWord), ("Word", WordSize::Word), ("Byte", WordSize::Byte)] {
+            word_row = word_row.push(
+                Radio::new(label, value, Some(context.word), Message::WordSize)
+                    .size(11)
+                    .spacing(3)
+                    .text_size(11)
+            );
+        }
+        Column::new().push(radix_row).push(word_row)
+    }
+
+    fn programmer_keys(&self) -> Column<'_, Message> {
+        let context = self.engine.context();
+        let digit = |label, c| key_enabled(label, Message::Num(c), ButtonType::Number, context.is_digit(c));
+        Column::new()
+            .spacing(1)
+            .push(Row::new().spacing(1).push(digit("A", 'A')).push(digit("B", 'B')).push(digit("C", 'C')))
+            .push(Row::new().spacing(1).push(digit("D", 'D')).push(digit("E", 'E')).push(digit("F", 'F')))
+            .push(
+                Row::new()
+                    .spacing(1)
+                    .push(key("And", Message::Operator(Operator::And), ButtonType::Operation))
+                    .push(key("Or", Message::Operator(Operator::Or), ButtonType::Operation))
+                    .push(key("Xor", Message::Operator(Operator::Xor), ButtonType::Operation))
+            )
+            .push(
+                Row::new()
+                    .spacing(1)
+                    .push(key("Lsh", Message::Operator(Operator::Lsh), ButtonType::Operation))
+                    .push(key("Rsh", Message::Operator(Operator::Rsh), ButtonType::Operation))
+                    .push(key("Not", Message::Special(Special::Not), ButtonType::Special))
+            )
+            .push(Row::new().spacing(1).push(key("Mod", Message::Operator(Operator::Mod), ButtonType::Operation)))
+    }
+
+    // Lista de datos: pulsar un valor lo lleva a la pantalla y × lo borra
+    fn data_box(&self) -> Element<'_, Message> {
+        let data = self.engine.data();
+        let radix = self.engine.context().radix;
+        let header = Row::new()
+            .spacing(5)
+            .push(Text::new(format!("n = {}", data.len())).size(11).width(Length::Fill))
+            .push(menu_button("CAD", Message::ClearData));
+        let mut entries = Column::new();
+        for (index, value) in data.into_iter().enumerate() {
+            entries = entries.push(
+                Row::new()
+                    .push(
+                        Button::new(Text::new(self.display_format().display(&value, radix)).size(11))
+                            .padding(0)
+                            .width(Length::Fill)
+                            .style(theme::Button::Text)
+                            .on_press(Message::LoadData(index))
+                    )
+                    .push(menu_button("×", Message::RemoveData(index)))
+            );
+        }
+        container(Column::new().spacing(2).push(header).push(Scrollable::new(entries)))
+            .padding(3)
+            .style(theme::Container::Custom(Box::new(ContainerStyle)))
+            .width(Length::Fixed(100.0))
+            .height(Length::Fixed(150.0))
+            .into()
+    }
+
+    fn statistics_keys(&self) -> Column<'_, Message> {
+        Column::new()
+            .spacing(1)
+            .push(
+                Row::new()
+                    .spacing(1)
+                    .push(key("Dat", Message::Dat, ButtonType::Memory))
+                    .push(key("n", Message::Stat(Stat::Count), ButtonType::Special))
+            )
+            .push(
+                Row::new()
+                    .spacing(1)
+                    .push(key("Ave", Message::Stat(Stat::Mean), ButtonType::Special))
+                    .push(key("Sum", Message::Stat(Stat::Sum), ButtonType::Special))
+            )
+            .push(
+                Row::new()
+                    .spacing(1)
+                    .push(key("s", Message::Stat(Stat::Deviation), ButtonType::Special))
+                    .push(key("Var", Message::Stat(Stat::Variance), ButtonType::Special))
+            )
+            .push(
+                Row::new()
+                    .spacing(1)
+                    .push(key("Med", Message::Stat(Stat::Median), ButtonType::Special))
+                    .push(key("Min", Message::Stat(Stat::Min), ButtonType::Special))
+            )
+            .push(Row::new().spacing(1).push(key("Max", Message::Stat(Stat::Max), ButtonType::Special)))
+    }
+
+    // Categoría, unidades de origen y destino y el número en pantalla ya
+    // convertido, que cambia con cada tecla
+    fn converter_box(&self) -> Element<'_, Message> {
+        let Conversion { category, from, to } = self.conversion;
+        let categories = units::categories();
+        let units = &categories[category].units;
+        let choices = |labels: Vec<String>| {
+            labels.into_iter().enumerate().map(|(index, label)| Choice { index, label }).collect::<Vec<_>>()
         };
-    
+        let unit_label = |unit: &Unit| format!("{} ({})", unit.name, unit.symbol);
+        let category_choices = choices(categories.iter().map(|c| c.name.clone()).collect());
+        let unit_choices = choices(units.iter().map(unit_label).collect());
+        let pick = |choices: &Vec<Choice>, selected: usize, message: fn(usize) -> Message| {
+            PickList::new(choices.clone(), choices.get(selected).cloned(), move |choice: Choice| message(choice.index))
+                .text_size(11)
+                .padding(2)
+                .width(Length::Fill)
+        };
+
+        let context = self.engine.context();
+        let result = match self.engine.number() {
+            Ok(value) if self.engine.error().is_none() => context
+                .convert_unit(&value, &units[from], &units[to])
+                .map(|result| self.display_format().display(&context.show(&context.format(&result)), Radix::Dec))
+                .unwrap_or_else(|error| error.to_string()),
+            _ => String::new(),
+        };
+
         container(
             Column::new()
-                .spacing(1)
-                .padding(1)
+                .spacing(3)
+                .push(pick(&category_choices, category, Message::Category))
+                .push(Text::new("De").size(10))
+                .push(pick(&unit_choices, from, Message::FromUnit))
+                .push(Text::new("A").size(10))
                 .push(
                     Row::new()
-                        .push(Text::new("Ver").size(11))
-                        .push(Text::new("Edición").size(11))
-                        .push(Text::new("Ayuda").size(11))
-                        .spacing(5)
-                        .padding(2)
+                        .spacing(2)
+                        .push(pick(&unit_choices, to, Message::ToUnit))
+                        .push(menu_button("⇅", Message::SwapUnits))
                 )
                 .push(
                     container(
-                        Text::new(display_text)
-                            .size(16)
+                        Text::new(format!("{} {}", result, units[to].symbol))
+                            .size(12)
                             .width(Length::Fill)
                             .horizontal_alignment(Horizontal::Right)
                     )
                     .padding(3)
                     .style(theme::Container::Custom(Box::new(ContainerStyle)))
                     .width(Length::Fill)
-                    .height(Length::Fixed(25.0))
-                )
-                .push(
-                    Row::new()
-                        .spacing(1)
-                        .push(
-                            Button::new(Text::new("←").size(12))
-                                .width(Length::Fixed(35.0))
-                                .style(theme::Button::Custom(Box::new(CustomButtonStyle { button_type: ButtonType::Clear })))
-                                .on_press(Message::Backspace)
-                        )
-                        .push(
-                            Button::new(Text::new("CE").size(12))
-                                .width(Length::Fixed(35.0))
-                                .style(theme::Button::Custom(Box::new(CustomButtonStyle { button_type: ButtonType::Clear })))
-                                .on_press(Message::ClearEnd)
-                        )
-                        .push(
-                            Button::new(Text::new("C").size(12))
-                                .width(Length::Fixed(35.0))
-                                .style(theme::Button::Custom(Box::new(CustomButtonStyle { button_type: ButtonType::Clear })))
-                                .on_press(Message::Clear)
-                        )
-                )
-                .push(
-                    Row::new()
-                        .spacing(1)
-                        .push(
-                            Button::new(Text::new("MC").size(12))
-                                .width(Length::Fixed(35.0))
-                                .style(theme::Button::Custom(Box::new(CustomButtonStyle { button_type: ButtonType::Memory })))
-                                .on_press(Message::Memory('C'))
-                        )
-                        .push(
-                            Button::new(Text::new("7").size(12))
-                                .width(Length::Fixed(35.0))
-                                .style(theme::Button::Custom(Box::new(CustomButtonStyle { button_type: ButtonType::Number })))
-                                .on_press(Message::Num('7'))
-                        )
-                        .push(
-                            Button::new(Text::new("8").size(12))
-                                .width(Length::Fixed(35.0))
-                                .style(theme::Button::Custom(Box::new(CustomButtonStyle { button_type: ButtonType::Number })))
-                                .on_press(Message::Num('8'))
-                        )
-                        .push(
-                            Button::new(Text::new("9").size(12))
-                                .width(Length::Fixed(35.0))
-                                .style(theme::Button::Custom(Box::new(CustomButtonStyle { button_type: ButtonType::Number })))
-                                .on_press(Message::Num('9'))
-                        )
-                        .push(
-                            Button::new(Text::new("/").size(12))
-                                .width(Length::Fixed(35.0))
-                                .style(theme::Button::Custom(Box::new(CustomButtonStyle { button_type: ButtonType::Operation })))
-                                .on_press(Message::Sign('÷'))
-                        )
-                        .push(
-                            Button::new(Text::new("sqrt").size(12))
-                                .width(Length::Fixed(35.0))
-                                .style(theme::Button::Custom(Box::new(CustomButtonStyle { button_type: ButtonType::Special })))
-                                .on_press(Message::Special('s'))
-                        )
-                )
-                .push(
-                    Row::new()
-                        .spacing(1)
-                        .push(
-                            Button::new(Text::new("MR").size(12))
-                                .width(Length::Fixed(35.0))
-                                .style(theme::Button::Custom(Box::new(CustomButtonStyle { button_type: ButtonType::Memory })))
-                                .on_press(Message::Memory('R'))
-                        )
-                        .push(
-                            Button::new(Text::new("4").size(12))
-                                .width(Length::Fixed(35.0))
-                                .style(theme::Button::Custom(Box::new(CustomButtonStyle { button_type: ButtonType::Number })))
-                                .on_press(Message::Num('4'))
-                        )
-                        .push(
-                            Button::new(Text::new("5").size(12))
-                                .width(Length::Fixed(35.0))
-                                .style(theme::Button::Custom(Box::new(CustomButtonStyle { button_type: ButtonType::Number })))
-                                .on_press(Message::Num('5'))
-                        )
-                        .push(
-                            Button::new(Text::new("6").size(12))
-                                .width(Length::Fixed(35.0))
-                                .style(theme::Button::Custom(Box::new(CustomButtonStyle { button_type: ButtonType::Number })))
-                                .on_press(Message::Num('6'))
-                        )
-                        .push(
-                            Button::new(Text::new("*").size(12))
-                                .width(Length::Fixed(35.0))
-                                .style(theme::Button::Custom(Box::new(CustomButtonStyle { button_type: ButtonType::Operation })))
-                                .on_press(Message::Sign('×'))
-                        )
-                        .push(
-                            Button::new(Text::new("%").size(12))
-                                .width(Length::Fixed(35.0))
-                                .style(theme::Button::Custom(Box::new(CustomButtonStyle { button_type: ButtonType::Special })))
-                                .on_press(Message::Special('%'))
-                        )
-                )
-                .push(
-                    Row::new()
-                        .spacing(1)
-                        .push(
-                            Button::new(Text::new("MS").size(12))
-                                .width(Length::Fixed(35.0))
-                                .style(theme::Button::Custom(Box::new(CustomButtonStyle { button_type: ButtonType::Memory })))
-                                .on_press(Message::Memory('S'))
-                        )
-                        .push(
-                            Button::new(Text::new("1").size(12))
-                                .width(Length::Fixed(35.0))
-                                .style(theme::Button::Custom(Box::new(CustomButtonStyle { button_type: ButtonType::Number })))
-                                .on_press(Message::Num('1'))
-                        )
-                        .push(
-                            Button::new(Text::new("2").size(12))
-                                .width(Length::Fixed(35.0))
-                                .style(theme::Button::Custom(Box::new(CustomButtonStyle { button_type: ButtonType::Number })))
-                                .on_press(Message::Num('2'))
-                        )
-                        .push(
-                            Button::new(Text::new("3").size(12))
-                                .width(Length::Fixed(35.0))
-                                .style(theme::Button::Custom(Box::new(CustomButtonStyle { button_type: ButtonType::Number })))
-                                .on_press(Message::Num('3'))
-                        )
-                        .push(
-                            Button::new(Text::new("-").size(12))
-                                .width(Length::Fixed(35.0))
-                                .style(theme::Button::Custom(Box::new(CustomButtonStyle { button_type: ButtonType::Operation })))
-                                .on_press(Message::Sign('-'))
-                        )
-                        .push(
-                            Button::new(Text::new("1/x").size(12))
-                                .width(Length::Fixed(35.0))
-                                .style(theme::Button::Custom(Box::new(CustomButtonStyle { button_type: ButtonType::Special })))
-                                .on_press(Message::Special('i'))
-                        )
-                )
-                .push(
-                    Row::new()
-                        .spacing(1)
-                        .push(
-                            Button::new(Text::new("M+").size(12))
-                                .width(Length::Fixed(35.0))
-                                .style(theme::Button::Custom(Box::new(CustomButtonStyle { button_type: ButtonType::Memory })))
-                                .on_press(Message::Memory('+'))
-                        )
-                        .push(
-                            Button::new(Text::new("0").size(12))
-                                .width(Length::Fixed(35.0))
-                                .style(theme::Button::Custom(Box::new(CustomButtonStyle { button_type: ButtonType::Number })))
-                                .on_press(Message::Num('0'))
-                        )
-                        .push(
-                            Button::new(Text::new("+/-").size(12))
-                                .width(Length::Fixed(35.0))
-                                .style(theme::Button::Custom(Box::new(CustomButtonStyle { button_type: ButtonType::Number })))
-                                .on_press(Message::Neg)
-                        )
-                        .push(
-                            Button::new(Text::new(".").size(12))
-                                .width(Length::Fixed(35.0))
-                                .style(theme::Button::Custom(Box::new(CustomButtonStyle { button_type: ButtonType::Number })))
-                                .on_press(Message::Dot)
-                        )
-                        .push(
-                            Button::new(Text::new("+").size(12))
-                                .width(Length::Fixed(35.0))
-                                .style(theme::Button::Custom(Box::new(CustomButtonStyle { button_type: ButtonType::Operation })))
-                                .on_press(Message::Sign('+'))
-                        )
-                        .push(
-                            Button::new(Text::new("=").size(12))
-                                .width(Length::Fixed(35.0))
-                                .style(theme::Button::Custom(Box::new(CustomButtonStyle { button_type: ButtonType::Equal })))
-                                .on_press(Message::Ans)
-                        )
                 )
         )
-        .style(theme::Container::Custom(Box::new(MainContainerStyle)))
+        .width(Length::Fixed(160.0))
+        .into()
+    }
+
+    // Teclado estándar: ←, CE, C y la rejilla de memoria, dígitos y operaciones.
+    // En modo programador se desactivan las cifras y teclas sin sentido entero.
+    fn keypad(&self) -> Column<'_, Message> {
+        let context = self.engine.context();
+        let decimal = self.mode != Mode::Programmer;
+        let digit = |label, c| key_enabled(label, Message::Num(c), ButtonType::Number, context.is_digit(c));
+        Column::new()
+            .spacing(1)
+            .push(
+                Row::new()
+                    .spacing(1)
+                    // Recuadro del indicador de memoria, como en XP
+                    .push(
+                        container(Text::new(if self.engine.has_memory() { "M" } else { "" }).size(12))
+                            .width(Length::Fixed(35.0))
+                            .center_x()
+                            .style(theme::Container::Custom(Box::new(ContainerStyle)))
+                    )
+                    .push(key("M-", Message::Memory(Memory::Subtract), ButtonType::Memory))
+                    .push(key("←", Message::Backspace, ButtonType::Clear))
+                    .push(key("CE", Message::ClearEnd, ButtonType::Clear))
+                    .push(key("C", Message::Clear, ButtonType::Clear))
+            )
+            .push(
+                Row::new()
+                    .spacing(1)
+                    .push(key("MC", Message::Memory(Memory::Clear), ButtonType::Memory))
+                    .push(digit("7", '7'))
+                    .push(digit("8", '8'))
+                    .push(digit("9", '9'))
+                    .push(key("/", Message::Operator(Operator::Divide), ButtonType::Operation))
+                    .push(key_enabled("sqrt", Message::Special(Special::Sqrt), ButtonType::Special, decimal))
+            )
+            .push(
+                Row::new()
+                    .spacing(1)
+                    .push(key("MR", Message::Memory(Memory::Recall), ButtonType::Memory))
+                    .push(digit("4", '4'))
+                    .push(digit("5", '5'))
+                    .push(digit("6", '6'))
+                    .push(key("*", Message::Operator(Operator::Multiply), ButtonType::Operation))
+                    .push(key_enabled("%", Message::Special(Special::Percent), ButtonType::Special, decimal))
+            )
+            .push(
+                Row::new()
+                    .spacing(1)
+                    .push(key("MS", Message::Memory(Memory::Store), ButtonType::Memory))
+                    .push(digit("1", '1'))
+                    .push(digit("2", '2'))
+                    .push(digit("3", '3'))
+                    .push(key("-", Message::Operator(Operator::Subtract), ButtonType::Operation))
+                    .push(key_enabled("1/x", Message::Special(Special::Reciprocal), ButtonType::Special, decimal))
+            )
+            .push(
+                Row::new()
+                    .spacing(1)
+                    .push(key("M+", Message::Memory(Memory::Add), ButtonType::Memory))
+                    .push(digit("0", '0'))
+                    .push(key("+/-", Message::Neg, ButtonType::Number))
+                    .push(key_enabled(".", Message::Dot, ButtonType::Number, decimal))
+                    .push(key("+", Message::Operator(Operator::Add), ButtonType::Operation))
+                    .push(key("=", Message::Ans, ButtonType::Equal))
+            )
+    }
+
+    // Variante de sin/cos/tan según las casillas Inv y Hyp
+    fn trig(&self, base: Function) -> Function {
+        use Function::*;
+        match (base, self.inverse, self.hyperbolic) {
+            (Sin, false, false) => Sin,
+            (Sin, true, false) => Asin,
+            (Sin, false, true) => Sinh,
+            (Sin, true, true) => Asinh,
+            (Cos, false, false) => Cos,
+            (Cos, true, false) => Acos,
+            (Cos, false, true) => Cosh,
+            (Cos, true, true) => Acosh,
+            (_, false, false) => Tan,
+            (_, true, false) => Atan,
+            (_, false, true) => Tanh,
+            (_, true, true) => Atanh,
+        }
+    }
+}
+
+// Atajos de teclado que se enseñan en Ayuda > Teclado
+const KEY_HELP: [(&str, &str); 15] = [
+    ("0-9, A-F", "Cifras"),
+    ("+ - * /", "Operaciones"),
+    ("Enter, =", "Resultado"),
+    ("Retroceso", "Borrar la última cifra"),
+    ("Esc", "C"),
+    ("Supr", "CE"),
+    (". ,", "Punto decimal"),
+    ("F9", "Cambiar el signo"),
+    ("@", "sqrt"),
+    ("r", "1/x"),
+    ("%", "Porcentaje"),
+    ("x", "Exp"),
+    ("Ctrl+L/R/M/P/Q", "MC, MR, MS, M+, M-"),
+    ("Ctrl+C/V", "Copiar y pegar"),
+    ("Ctrl+Z/Y", "Deshacer y rehacer"),
+];
+
+fn dialog_box<'a>(dialog: Dialog) -> Element<'a, Message> {
+    let body: Element<'a, Message> = match dialog {
+        Dialog::About => Column::new()
+            .spacing(4)
+            .push(Text::new("RustiCalculadora").size(14))
+            .push(Text::new(format!("Versión {}", env!("CARGO_PKG_VERSION"))).size(11))
+            .push(Text::new("La calculadora de Windows XP, en Rust con Iced.").size(11))
+            .into(),
+        Dialog::Keys => {
+            let mut rows = Column::new().spacing(1);
+            for (keys, action) in KEY_HELP {
+                rows = rows.push(
+                    Row::new()
+                        .push(Text::new(keys).size(10).width(Length::Fixed(80.0)))
+                        .push(Text::new(action).size(10))
+                );
+            }
+            Scrollable::new(rows).height(Length::Fill).into()
+        },
+    };
+    Column::new()
+        .spacing(4)
+        .padding(4)
+        .push(body)
+        .push(menu_button("Cerrar", Message::Dialog(None)))
         .into()
+}
+
+// Teclado físico con los mismos atajos que la calculadora de XP. Las teclas
+// con nombre llegan como KeyPressed y el resto como el carácter escrito.
+fn keyboard_message(event: Event, status: event::Status) -> Option<Message> {
+    if status == event::Status::Captured {
+        return None;
+    }
+    match event {
+        Event::Keyboard(keyboard::Event::KeyPressed { key_code, modifiers }) if modifiers.control() => {
+            match key_code {
+                KeyCode::C => Some(Message::Copy),
+                KeyCode::V => Some(Message::Paste),
+                KeyCode::Z => Some(Message::Undo),
+                KeyCode::Y => Some(Message::Redo),
+                KeyCode::L => Some(Message::Memory(Memory::Clear)), // MC
+                KeyCode::R => Some(Message::Memory(Memory::Recall)), // MR
+                KeyCode::M => Some(Message::Memory(Memory::Store)), // MS
+                KeyCode::P => Some(Message::Memory(Memory::Add)), // M+
+                KeyCode::Q => Some(Message::Memory(Memory::Subtract)), // M-
+                _ => None,
+            }
+        },
+        Event::Keyboard(keyboard::Event::KeyPressed { key_code, .. }) => match key_code {
+            KeyCode::Enter | KeyCode::NumpadEnter => Some(Message::Ans),
+            KeyCode::Backspace => Some(Message::Backspace),
+            KeyCode::Escape => Some(Message::Clear),
+            KeyCode::Delete => Some(Message::ClearEnd),
+            KeyCode::F9 => Some(Message::Neg),
+            _ => None,
+        },
+        Event::Keyboard(keyboard::Event::CharacterReceived(c)) => match c {
+            '0'..='9' => Some(Message::Num(c)),
+            'a'..='f' | 'A'..='F' => Some(Message::Num(c.to_ascii_uppercase())), // cifras hexadecimales
+            '+' => Some(Message::Operator(Operator::Add)),
+            '-' => Some(Message::Operator(Operator::Subtract)),
+            '*' => Some(Message::Operator(Operator::Multiply)),
+            '/' => Some(Message::Operator(Operator::Divide)),
+            '=' => Some(Message::Ans),
+            '.' | ',' => Some(Message::Dot),
+            '@' => Some(Message::Special(Special::Sqrt)),
+            'r' => Some(Message::Special(Special::Reciprocal)),
+            '%' => Some(Message::Special(Special::Percent)),
+            '(' => Some(Message::Open),
+            ')' => Some(Message::Close),
+            'x' => Some(Message::Exp),
+            _ => None,
+        },
+        _ => None,
     }
+}
+
+// Opciones del menú Ver > Cifras en pantalla
+const DIGIT_LIMITS: [usize; 4] = [12, 16, 20, 32];
+
+// Pasos que se pueden deshacer
+const UNDO_LIMIT: usize = 100;
 
+// Ancho extra de la ventana por cada panel abierto (historial, memoria)
+const PANEL_WIDTH: u32 = 150;
+
+// Tamaño de la ventana para cada vista
+pub fn window_size(mode: Mode) -> (u32, u32) {
+    match mode {
+        Mode::Standard => (230, 200),
+        Mode::Scientific => (345, 250),
+        Mode::Programmer => (345, 245),
+        Mode::Statistics => (420, 200),
+        Mode::Converter => (400, 200),
+    }
 }
+
 pub struct MainContainerStyle;
 
 impl container::StyleSheet for MainContainerStyle {
@@ -0,0 +1,195 @@
+// Preferencias, memoria e historial que se conservan entre sesiones, en
+// $XDG_CONFIG_HOME/rusticalculadora/config (o ~/.config si no está definido).
+// Es un fichero de texto "clave=valor"; si algo no se entiende se descarta
+// entero y se arranca con los valores por defecto.
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use calculadora_core::units;
+use calculadora_core::{Arithmetic, Context, Entry, Format, Register};
+
+use crate::calculator::{Conversion, Mode};
+
+const MODES: [(&str, Mode); 5] = [
+    ("standard", Mode::Standard),
+    ("scientific", Mode::Scientific),
+    ("programmer", Mode::Programmer),
+    ("statistics", Mode::Statistics),
+    ("converter", Mode::Converter),
+];
+const SEPARATORS: [(&str, char); 3] = [("point", '.'), ("comma", ','), ("space", ' ')];
+const BOOLS: [(&str, bool); 2] = [("true", true), ("false", false)];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    pub mode: Mode,
+    pub context: Context, // con la aritmética de fuera del modo programador
+    pub format: Format,
+    pub conversion: Conversion,
+    pub memory: Vec<Register>,
+    pub history: Vec<Entry>,
+}
+
+impl Config {
+    // None si no hay fichero o no se puede leer
+    pub fn load() -> Option<Config> {
+        let text = fs::read_to_string(path()?).ok()?;
+        Config::parse(&text)
+    }
+
+    // Se escribe aparte y se renombra para no dejar un fichero a medias
+    pub fn save(&self) -> io::Result<()> {
+        let path = path().ok_or(io::ErrorKind::NotFound)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let temporary = path.with_extension("tmp");
+        fs::write(&temporary, self.to_text())?;
+        fs::rename(temporary, path)
+    }
+
+    fn parse(text: &str) -> Option<Config> {
+        let mut mode = Mode::Standard;
+        let mut context = Context::default();
+        let mut format = Format::default();
+        let mut conversion = Conversion::default();
+        let mut memory = Vec::new();
+        let mut history = Vec::new();
+        for line in text.lines().filter(|line| !line.trim().is_empty() && !line.starts_with('#')) {
+            let (key, value) = line.split_once('=')?;
+            match key.trim() {
+                "mode" => mode = lookup(&MODES, value)?,
+                // La entera es solo la del modo programador, que no se guarda
+                "arithmetic" => context.arithmetic = value.trim().parse().ok().filter(|a| *a != Arithmetic::Integer)?,
+                "fractions" => context.fractions = lookup(&BOOLS, value)?,
+                "precision" => context.precision = value.trim().parse().ok().filter(|p| *p > 0)?,
+                "angle" => context.angle = value.trim().parse().ok()?,
+                "radix" => context.radix = value.trim().parse().ok()?,
+                "word" => context.word = value.trim().parse().ok()?,
+                "grouping" => format.grouping = lookup(&BOOLS, value)?,
+                "decimal" => format.decimal = lookup(&SEPARATORS, value)?,
+                "thousands" => format.thousands = lookup(&SEPARATORS, value)?,
+                "exponent" => format.exponent = lookup(&BOOLS, value)?,
+                "digits" => format.digits = value.trim().parse().ok().filter(|d| *d > 0)?,
+                // Si la tabla de unidades ya no tiene alguna se vuelve a la conversión por defecto
+                "units" => conversion = conversion_of(value).unwrap_or_default(),
+                // nombre|valor; los ficheros de antes solo tienen el valor
+                "memory" => {
+                    let (name, value) = match value.rsplit_once('|') {
+                        Some((name, value)) => (name.to_string(), value),
+                        None if memory.is_empty() => ("M".to_string(), value),
+                        None => (format!("M{}", memory.len()), value),
+                    };
+                    memory.push(Register { name, value: value.trim().to_string() });
+                },
+                "history" => {
+                    let (tokens, result) = value.rsplit_once(" = ")?;
+                    let tokens = tokens.split_whitespace().map(str::to_string).collect();
+                    history.push(Entry { tokens, result: result.trim().to_string() });
+                },
+                _ => return None,
+            }
+        }
+        // Los números tienen que poder leerse con la aritmética guardada, y las
+        // operaciones del historial, volver a escribirse en la calculadora
+        if memory.iter().any(|register| context.parse(&register.value).is_err()) || !history.iter().all(|entry| entry.is_valid(&context)) {
+            return None;
+        }
+        Some(Config { mode, context, format, conversion, memory, history })
+    }
+
+    fn to_text(&self) -> String {
+        let context = &self.context;
+        let format = &self.format;
+        let mut lines = vec![
+            "# RustiCalculadora: se reescribe al cambiar cualquier ajuste".to_string(),
+            format!("mode={}", name(&MODES, self.mode)),
+            format!("arithmetic={}", context.arithmetic.name()),
+            format!("fractions={}", context.fractions),
+            format!("precision={}", context.precision),
+            format!("angle={}", context.angle.name()),
+            format!("radix={}", context.radix.name()),
+            format!("word={}", context.word.name()),
+            format!("grouping={}", format.grouping),
+            format!("decimal={}", name(&SEPARATORS, format.decimal)),
+            format!("thousands={}", name(&SEPARATORS, format.thousands)),
+            format!("exponent={}", format.exponent),
+            format!("digits={}", format.digits),
+        ];
+        // Por nombre, para que siga valiendo si se añaden unidades a la tabla
+        let category = &units::categories()[self.conversion.category];
+        let unit = |index: usize| &category.units[index].name;
+        lines.push(format!("units={}|{}|{}", category.name, unit(self.conversion.from), unit(self.conversion.to)));
+        lines.extend(self.memory.iter().map(|register| format!("memory={}|{}", register.name, register.value)));
+        for entry in &self.history {
+            lines.push(format!("history={} = {}", entry.tokens.join(" "), entry.result));
+        }
+        lines.join("\n") + "\n"
+    }
+}
+
+fn path() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_CONFIG_HOME").map(PathBuf::from) {
+        Some(dir) if dir.is_absolute() => dir,
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join("rusticalculadora").join("config"))
+}
+
+// Categoría|origen|destino por nombre, como se guarda
+fn conversion_of(value: &str) -> Option<Conversion> {
+    let [category, from, to] = value.trim().split('|').collect::<Vec<_>>()[..] else {
+        return None;
+    };
+    let categories = units::categories();
+    let index = categories.iter().position(|c| c.name == category)?;
+    let unit = |name: &str| categories[index].units.iter().position(|u| u.name == name);
+    Some(Conversion { category: index, from: unit(from)?, to: unit(to)? })
+}
+
+fn lookup<T: Copy>(table: &[(&str, T)], name: &str) -> Option<T> {
+    table.iter().find(|(key, _)| *key == name.trim()).map(|(_, value)| *value)
+}
+
+fn name<T: PartialEq>(table: &[(&'static str, T)], value: T) -> &'static str {
+    table.iter().find(|(_, v)| *v == value).map(|(key, _)| *key).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn written_config_reads_back() {
+        let config = Config::parse("mode=scientific\narithmetic=rational\nmemory=IVA|21/100\nhistory=2 + 3 = 5\n").unwrap();
+        assert_eq!(config.mode, Mode::Scientific);
+        assert_eq!(config.memory[0], Register { name: "IVA".to_string(), value: "21/100".to_string() });
+        assert_eq!(Config::parse(&config.to_text()), Some(config));
+    }
+
+    #[test]
+    fn corrupted_files_are_rejected() {
+        for text in [
+            "mode=nueva",
+            "precision=0",
+            "color=azul",
+            "sin igual",
+            "memory=M|abc",
+            "history=2 + 3",
+            "history=2 + x = 5",
+            "history=) ) = 5",
+            "history=( 2 + 3 = 5",
+            "arithmetic=decimal\nmemory=M|1/3",
+        ] {
+            assert_eq!(Config::parse(text), None, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn unknown_units_fall_back_to_the_default() {
+        let config = Config::parse("units=Longitud|metro|codo\n").unwrap();
+        assert_eq!(config.conversion, Conversion::default());
+    }
+}
@@ -1,13 +1,14 @@
 mod calculator;
+mod config;
 mod ui;
 
-use calculator::Calculator;
-use iced::{Sandbox, Settings};
+use calculator::{window_size, Calculator, Mode};
+use iced::{Application, Settings};
 
 fn main() -> iced::Result {
     Calculator::run(Settings {
         window: iced::window::Settings { 
-            size: (230, 200), // Tamaño más compacto como la referencia
+            size: window_size(Mode::Standard), // Tamaño más compacto como la referencia
             resizable: false,
             decorations: true,
             ..iced::window::Settings::default()
@@ -1,6 +1,7 @@
 use iced::widget::button;
-use iced::{Background, Color, Vector, theme};
+use iced::{Background, Color, Vector};
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, Copy)]
 pub enum Button {
     Ans,
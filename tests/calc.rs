@@ -0,0 +1,41 @@
+// Pruebas de la calculadora de línea de órdenes, ejecutando el binario calc
+
+use std::process::{Command, Output};
+use std::time::{Duration, Instant};
+
+fn calc(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_calc")).args(args).output().unwrap()
+}
+
+#[test]
+fn huge_exponents_overflow_at_once() {
+    for expression in ["1e9999999+1", "1e-9999999+1", "1e20000"] {
+        for exact in [&[][..], &["--exact"][..]] {
+            let start = Instant::now();
+            let output = calc(&[exact, &[expression]].concat());
+            assert!(start.elapsed() < Duration::from_secs(5), "{} tarda demasiado", expression);
+            assert_eq!(output.status.code(), Some(1), "{}", expression);
+            assert!(String::from_utf8_lossy(&output.stderr).contains("Desbordamiento"), "{}", expression);
+        }
+    }
+}
+
+// Lo que calc escribe en la salida estándar, sin el salto de línea
+fn result(args: &[&str]) -> String {
+    let output = calc(args);
+    assert!(output.status.success(), "{:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap().trim_end().to_string()
+}
+
+#[test]
+fn precision_shows_every_digit() {
+    assert_eq!(result(&["-p", "50", "1/7"]), format!("0.{}", "142857".repeat(8)) + "14");
+}
+
+#[test]
+fn integer_modes_and_word_size() {
+    assert_eq!(result(&["--mode", "hex", "ff + 1"]), "100");
+    assert_eq!(result(&["-m", "bin", "-w", "byte", "11111111 + 1"]), "0");
+    assert_eq!(result(&["-w", "word", "-m", "dec", "32767 + 1"]), "-32768");
+    assert_eq!(calc(&["-m", "hexa", "1"]).status.code(), Some(2));
+}